@@ -4,15 +4,20 @@ use bitmask_enum::bitmask;
 enum Bitmask {
     Flag1, // defaults to 0b00000001
 
+    #[bitmask_flag(composite)]
     CustomFlag3 = 0b00000100,
 
     Flag2, // defaults to 0b00000010
     Flag3, // defaults to 0b00000100
 
+    #[bitmask_flag(composite)]
     Flag13_1 = 0b00000001 | 0b00000100,
+    #[bitmask_flag(composite)]
     Flag13_2 = Self::Flag1.or(Self::Flag3).bits,
+    #[bitmask_flag(composite)]
     Flag13_3 = Self::Flag1.bits | Self::CustomFlag3.bits,
 
+    #[bitmask_flag(composite)]
     Flag123 = {
         let flag13 = Self::Flag13_1.bits;
         flag13 | Self::Flag2.bits
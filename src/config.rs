@@ -0,0 +1,508 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Error, Expr, ExprLit, Ident, Lit, LitInt, LitStr, Meta, Path, Result, Token,
+};
+
+/// The traits derived for every bitmask unless removed with `no_derive(...)`.
+pub const DERIVES: &[&str] = &["Clone", "Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash"];
+
+/// The derived traits the generated code relies on.
+const REQUIRED_DERIVES: &[&str] = &["Clone", "Copy", "PartialEq"];
+
+/// The derived traits that other derived traits rely on, as `(required, dependent)`.
+const DERIVE_REQUIREMENTS: &[(&str, &str)] = &[("Eq", "Ord"), ("PartialOrd", "Ord")];
+
+/// The traits implemented by the generated code that can not be derived.
+const IMPLEMENTED: &[(&str, &str)] = &[
+    ("Debug", "`Debug` is implemented by the generated code, use `debug = \"vec\"` to change it"),
+    ("Default", "`Default` is implemented with the `default` config option"),
+];
+
+/// The options of the `#[bitmask_config(...)]` attribute.
+pub struct Config {
+    pub inverted_flags: bool,
+    pub inverted_prefix: String,
+    pub vec_debug: bool,
+    pub flags_iter: bool,
+    pub display: bool,
+    pub serde: Option<UnknownBits>,
+    pub atomic: bool,
+    pub flag_enum: bool,
+    pub strict: bool,
+    pub bytemuck: bool,
+    pub zerocopy: bool,
+    pub defmt: bool,
+    pub ufmt: bool,
+    /// The chance in percent of generating undeclared bits.
+    pub arbitrary: Option<u8>,
+    /// The chance in percent of generating undeclared bits.
+    pub proptest: Option<u8>,
+    pub schemars: Option<SchemaFormat>,
+    pub derive: Vec<Path>,
+    pub no_derive: Vec<Ident>,
+    pub default: Option<Vec<Ident>>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self {
+            inverted_flags: false,
+            inverted_prefix: String::from("Inverted"),
+            vec_debug: false,
+            flags_iter: false,
+            display: false,
+            serde: None,
+            atomic: false,
+            flag_enum: false,
+            strict: false,
+            bytemuck: false,
+            zerocopy: false,
+            defmt: false,
+            ufmt: false,
+            arbitrary: None,
+            proptest: None,
+            schemars: None,
+            derive: Vec::new(),
+            no_derive: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Returns `true` if `name` is one of the `DERIVES` and not removed with `no_derive(...)`.
+    pub fn derives(&self, name: &str) -> bool {
+        DERIVES.contains(&name) && !self.no_derive.iter().any(|ident| ident == name)
+    }
+}
+
+/// How deserialization handles bits that do not correspond to a flag.
+#[derive(Clone, Copy)]
+pub enum UnknownBits {
+    Reject,
+    Truncate,
+    Retain,
+}
+
+/// How the JSON Schema describes the bitmask.
+#[derive(Clone, Copy)]
+pub enum SchemaFormat {
+    Names,
+    Bits,
+}
+
+impl Parse for Config {
+    fn parse(input: ParseStream) -> Result<Self> {
+        // Options that set the same value share a key,
+        // so they are reported as duplicates of each other.
+        const OPTIONS: &[(&str, &str)] = &[
+            ("inverted_flags", "inverted_flags"),
+            ("inverted_prefix", "inverted_prefix"),
+            ("vec_debug", "debug"),
+            ("debug", "debug"),
+            ("flags_iter", "flags_iter"),
+            ("display", "display"),
+            ("serde", "serde"),
+            ("atomic", "atomic"),
+            ("flag_enum", "flag_enum"),
+            ("strict", "strict"),
+            ("bytemuck", "bytemuck"),
+            ("zerocopy", "zerocopy"),
+            ("defmt", "defmt"),
+            ("ufmt", "ufmt"),
+            ("arbitrary", "arbitrary"),
+            ("proptest", "proptest"),
+            ("schemars", "schemars"),
+            ("derive", "derive"),
+            ("no_derive", "no_derive"),
+            ("default", "default"),
+        ];
+
+        let args = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        let mut config = Self::new();
+        let mut keys = Vec::with_capacity(args.len());
+        let mut retain = None;
+        let mut from_bytes = Vec::new();
+        let mut unknown_chances = Vec::<LitInt>::new();
+        for arg in args {
+            let ident = arg.path().require_ident()?;
+            let name = ident.to_string();
+
+            let Some(&(_, key)) = OPTIONS.iter().find(|(option, _)| *option == name) else {
+                return Err(unknown_option(
+                    ident,
+                    "config option",
+                    OPTIONS.iter().map(|(option, _)| *option),
+                ));
+            };
+            if keys.contains(&key) {
+                return Err(Error::new_spanned(
+                    ident,
+                    format!("duplicate config option `{}`", key),
+                ));
+            }
+            keys.push(key);
+
+            match name.as_str() {
+                "inverted_flags" => {
+                    arg.require_path_only()?;
+                    config.inverted_flags = true;
+                }
+                "inverted_prefix" => {
+                    let prefix = lit_str(&arg)?;
+                    if syn::parse_str::<Ident>(&prefix.value()).is_err() {
+                        return Err(Error::new_spanned(prefix, "expected an identifier prefix"));
+                    }
+                    config.inverted_flags = true;
+                    config.inverted_prefix = prefix.value();
+                }
+                "vec_debug" => {
+                    arg.require_path_only()?;
+                    config.vec_debug = true;
+                }
+                "debug" => {
+                    let debug = lit_str(&arg)?;
+                    config.vec_debug = match debug.value().as_str() {
+                        "vec" => true,
+                        "struct" => false,
+                        _ => {
+                            return Err(Error::new_spanned(debug, r#"expected "vec" or "struct""#))
+                        }
+                    };
+                }
+                "flags_iter" => {
+                    arg.require_path_only()?;
+                    config.flags_iter = true;
+                }
+                "display" => {
+                    if !matches!(arg, Meta::Path(_)) {
+                        let display = lit_str(&arg)?;
+                        if display.value() != "names" {
+                            return Err(Error::new_spanned(display, r#"expected "names""#));
+                        }
+                    }
+                    config.display = true;
+                }
+                "serde" => {
+                    config.serde = Some(UnknownBits::Reject);
+                    if let Meta::List(list) = &arg {
+                        let args =
+                            list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+                        for arg in args {
+                            let ident = arg.path().require_ident()?;
+                            if ident != "unknown_bits" {
+                                return Err(unknown_option(
+                                    ident,
+                                    "serde option",
+                                    ["unknown_bits"],
+                                ));
+                            }
+
+                            let unknown_bits = lit_str(&arg)?;
+                            config.serde = Some(match unknown_bits.value().as_str() {
+                                "reject" => UnknownBits::Reject,
+                                "truncate" => UnknownBits::Truncate,
+                                "retain" => {
+                                    retain = Some(unknown_bits.span());
+                                    UnknownBits::Retain
+                                }
+                                _ => {
+                                    return Err(Error::new_spanned(
+                                        unknown_bits,
+                                        r#"expected "reject", "truncate" or "retain""#,
+                                    ))
+                                }
+                            });
+                        }
+                    } else {
+                        arg.require_path_only()?;
+                    }
+                }
+                "atomic" => {
+                    arg.require_path_only()?;
+                    config.atomic = true;
+                }
+                "flag_enum" => {
+                    arg.require_path_only()?;
+                    config.flag_enum = true;
+                }
+                "strict" => {
+                    arg.require_path_only()?;
+                    config.strict = true;
+                }
+                "bytemuck" => {
+                    arg.require_path_only()?;
+                    from_bytes.push(ident.clone());
+                    config.bytemuck = true;
+                }
+                "zerocopy" => {
+                    arg.require_path_only()?;
+                    from_bytes.push(ident.clone());
+                    config.zerocopy = true;
+                }
+                "defmt" => {
+                    arg.require_path_only()?;
+                    config.defmt = true;
+                }
+                "ufmt" => {
+                    arg.require_path_only()?;
+                    config.ufmt = true;
+                }
+                "arbitrary" | "proptest" => {
+                    let mut chance = None;
+                    if let Meta::List(list) = &arg {
+                        let args =
+                            list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+                        for arg in args {
+                            let ident = arg.path().require_ident()?;
+                            if ident != "unknown_bits" {
+                                return Err(unknown_option(
+                                    ident,
+                                    &format!("{} option", name),
+                                    ["unknown_bits"],
+                                ));
+                            }
+                            chance = Some(percent(&arg)?);
+                        }
+                    } else {
+                        arg.require_path_only()?;
+                    }
+                    if let Some((_, lit)) = &chance {
+                        unknown_chances.push(lit.clone());
+                    }
+
+                    let chance = chance.map(|(chance, _)| chance);
+                    if name == "arbitrary" {
+                        config.arbitrary = Some(chance.unwrap_or(10));
+                    } else {
+                        config.proptest = Some(chance.unwrap_or(10));
+                    }
+                }
+                "schemars" => {
+                    config.schemars = Some(SchemaFormat::Names);
+                    if !matches!(arg, Meta::Path(_)) {
+                        let format = lit_str(&arg)?;
+                        config.schemars = Some(match format.value().as_str() {
+                            "names" => SchemaFormat::Names,
+                            "bits" => SchemaFormat::Bits,
+                            _ => {
+                                return Err(Error::new_spanned(
+                                    format,
+                                    r#"expected "names" or "bits""#,
+                                ))
+                            }
+                        });
+                    }
+                }
+                "derive" => {
+                    let paths = arg
+                        .require_list()?
+                        .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+                    for path in paths {
+                        if let Some(ident) = path.get_ident() {
+                            let name = ident.to_string();
+                            if DERIVES.contains(&name.as_str()) {
+                                return Err(Error::new_spanned(
+                                    ident,
+                                    format!("`{}` is derived by default", name),
+                                ));
+                            }
+                            if let Some(&(_, msg)) =
+                                IMPLEMENTED.iter().find(|(trait_, _)| *trait_ == name)
+                            {
+                                return Err(Error::new_spanned(ident, msg));
+                            }
+                        }
+                        config.derive.push(path);
+                    }
+                }
+                "no_derive" => {
+                    let idents = arg
+                        .require_list()?
+                        .parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+                    for ident in idents {
+                        let name = ident.to_string();
+                        if REQUIRED_DERIVES.contains(&name.as_str()) {
+                            return Err(Error::new_spanned(
+                                ident,
+                                format!("`{}` is required by the generated code", name),
+                            ));
+                        }
+                        if !DERIVES.contains(&name.as_str()) {
+                            return Err(unknown_option(
+                                &ident,
+                                "derived trait",
+                                DERIVES.iter().copied(),
+                            ));
+                        }
+                        config.no_derive.push(ident);
+                    }
+
+                    for ident in &config.no_derive {
+                        if let Some(&(_, dependent)) =
+                            DERIVE_REQUIREMENTS.iter().find(|(required, dependent)| {
+                                ident == required && config.derives(dependent)
+                            })
+                        {
+                            return Err(Error::new_spanned(
+                                ident,
+                                format!(
+                                    "`{}` is required by `{}`, remove `{}` as well",
+                                    ident, dependent, dependent
+                                ),
+                            ));
+                        }
+                    }
+                }
+                "default" => {
+                    config.default = Some(match &arg {
+                        Meta::Path(_) => Vec::new(),
+                        _ => arg
+                            .require_list()?
+                            .parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?
+                            .into_iter()
+                            .collect(),
+                    });
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        if let (true, Some(span)) = (config.strict, retain) {
+            return Err(Error::new(
+                span,
+                "unknown bits can not be retained by a `strict` bitmask",
+            ));
+        }
+        if config.strict {
+            if let Some(lit) = unknown_chances.iter().find(|lit| lit.base10_digits() != "0") {
+                return Err(Error::new_spanned(
+                    lit,
+                    "a `strict` bitmask can not contain unknown bits",
+                ));
+            }
+            // Unknown bits are never generated for strict bitmasks.
+            config.arbitrary = config.arbitrary.map(|_| 0);
+            config.proptest = config.proptest.map(|_| 0);
+        }
+        if let (true, Some(ident)) = (config.strict, from_bytes.first()) {
+            return Err(Error::new_spanned(
+                ident,
+                format!("`{}` can not be combined with `strict`, as any bytes can be cast to the bitmask", ident),
+            ));
+        }
+        Ok(config)
+    }
+}
+
+/// The options of the `#[bitmask_flag(...)]` attribute of a variant.
+pub struct FlagConfig {
+    pub composite: bool,
+}
+
+impl FlagConfig {
+    pub fn new() -> Self {
+        Self { composite: false }
+    }
+
+    /// Parses the `#[bitmask_flag(...)]` attributes of a variant,
+    /// returning the remaining attributes.
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<(Self, Vec<Attribute>)> {
+        const OPTIONS: &[&str] = &["composite"];
+
+        let mut config = Self::new();
+        let mut rest = Vec::with_capacity(attrs.len());
+        for attr in attrs {
+            if !attr.path().is_ident("bitmask_flag") {
+                rest.push(attr.clone());
+                continue;
+            }
+
+            let args = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            for arg in args {
+                let ident = arg.path().require_ident()?;
+                match ident.to_string().as_str() {
+                    "composite" => {
+                        arg.require_path_only()?;
+                        config.composite = true;
+                    }
+                    _ => {
+                        return Err(unknown_option(
+                            ident,
+                            "flag option",
+                            OPTIONS.iter().copied(),
+                        ))
+                    }
+                }
+            }
+        }
+        Ok((config, rest))
+    }
+}
+
+/// Returns the string literal value of a `key = "value"` option.
+fn lit_str(meta: &Meta) -> Result<LitStr> {
+    match &meta.require_name_value()?.value {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(lit) => Ok(lit.clone()),
+            lit => Err(Error::new_spanned(lit, "expected a string literal")),
+        },
+        expr => Err(Error::new_spanned(expr, "expected a string literal")),
+    }
+}
+
+/// Returns the value of a `key = 10` option as a percentage from 0 to 100.
+fn percent(meta: &Meta) -> Result<(u8, LitInt)> {
+    match &meta.require_name_value()?.value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => match lit.base10_parse::<u8>() {
+            Ok(percent) if percent <= 100 => Ok((percent, lit.clone())),
+            _ => Err(Error::new_spanned(lit, "expected a percentage from 0 to 100")),
+        },
+        expr => Err(Error::new_spanned(expr, "expected a percentage from 0 to 100")),
+    }
+}
+
+/// Returns an error for an unknown option,
+/// suggesting the closest known option if there is a similar one.
+fn unknown_option<'a>(
+    ident: &Ident,
+    what: &str,
+    options: impl IntoIterator<Item = &'a str>,
+) -> Error {
+    let name = ident.to_string();
+    let closest = options
+        .into_iter()
+        .map(|option| (edit_distance(&name, option), option))
+        .min();
+
+    match closest {
+        Some((distance, option)) if distance <= option.len().max(name.len()) / 3 + 1 => {
+            Error::new_spanned(
+                ident,
+                format!("unknown {} `{}`, did you mean `{}`?", what, name, option),
+            )
+        }
+        _ => Error::new_spanned(ident, format!("unknown {} `{}`", what, name)),
+    }
+}
+
+/// Returns the Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                prev.min(cur).min(row[j]) + 1
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
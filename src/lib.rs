@@ -1,13 +1,16 @@
 #![doc = include_str!("../README.md")]
 
+mod config;
 mod parser;
+mod typ;
 
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, ItemEnum};
 
 /// ## Bitmask-Enum
 ///
-/// A bitmask can have unsigned integer types, the default type is `usize`.
+/// A bitmask can have (un)signed integer types, the default type is `usize`.
+/// For more flags than fit into an integer it can be an array of unsigned integers.
 ///
 /// ```
 /// use bitmask_enum::bitmask;
@@ -17,6 +20,9 @@ use syn::{parse_macro_input, ItemEnum};
 ///
 /// #[bitmask(u8)] // u8
 /// enum BitmaskU8 { /* ... */ }
+///
+/// #[bitmask([u64; 4])] // 256 bits
+/// enum BitmaskWide { /* ... */ }
 /// ```
 #[proc_macro_attribute]
 pub fn bitmask(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -1,12 +1,15 @@
-use proc_macro::{Span, TokenStream};
-use syn::{
-    parse::{Parse, ParseStream},
-    punctuated::Punctuated,
-    Error, Ident, ItemEnum, Result, Token,
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{Attribute, Expr, ExprLit, Ident, ItemEnum, Lit, Meta, Result};
+
+use crate::{
+    config::{Config, FlagConfig, SchemaFormat, UnknownBits, DERIVES},
+    typ::Typ,
 };
 
 pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
-    let typ = parse_typ(attr)?;
+    let typ = Typ::parse(attr)?;
 
     let config = if let Some(idx) = item
         .attrs
@@ -23,134 +26,1664 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
     let attrs = item.attrs;
     let ident = item.ident;
 
-    let mut flags_amount = item.variants.len();
+    let mut capacity = item.variants.len();
 
     if config.inverted_flags {
-        flags_amount *= 2;
+        capacity *= 2;
     }
 
-    let mut all_flags = Vec::with_capacity(flags_amount);
-    let mut all_flags_names = Vec::with_capacity(flags_amount);
+    // Flags and variants can be disabled by `#[cfg]` attributes,
+    // so their amounts are only known as const expressions.
+    let mut flags_amount = quote::quote!(0);
+    let mut variants_amount = quote::quote!(0);
+
+    let mut all_flags = Vec::with_capacity(capacity);
+    let mut all_flags_names = Vec::with_capacity(capacity);
+    let mut all_flags_cfgs = Vec::with_capacity(capacity);
+    let mut all_flags_inverted = Vec::with_capacity(capacity);
+    let mut all_flags_composite = Vec::with_capacity(capacity);
+    let mut all_flags_docs = Vec::with_capacity(capacity);
+
+    let mut inverted_amount = quote::quote!(0);
+    let mut inverted = Vec::with_capacity(item.variants.len());
+    let mut inverted_names = Vec::with_capacity(item.variants.len());
+    let mut inverted_cfgs = Vec::with_capacity(item.variants.len());
 
-    let mut i: usize = 0;
-    let mut flags = Vec::with_capacity(flags_amount);
+    let mut variants = Vec::with_capacity(item.variants.len());
+    let mut variants_cfgs = Vec::with_capacity(item.variants.len());
+    let mut variants_docs = Vec::with_capacity(item.variants.len());
+
+    let mut shift = quote::quote!(0);
+    let mut flags = Vec::with_capacity(capacity);
+    let mut flag_checks = Vec::with_capacity(item.variants.len());
     for v in item.variants.iter() {
-        let v_attrs = &v.attrs;
+        let (flag_config, v_attrs) = FlagConfig::from_attrs(&v.attrs)?;
         let v_ident = &v.ident;
+        let v_span = v_ident.span();
+
+        let cfgs = v_attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .collect::<Vec<_>>();
+        let v_cfg = quote::quote!(#(#cfgs)*);
+        let v_count = cfg_count(&cfgs)?;
+
+        flags_amount.extend(quote::quote!(+ #v_count));
+        variants_amount.extend(quote::quote!(+ #v_count));
 
+        variants.push(v_ident.clone());
+        variants_cfgs.push(v_cfg.clone());
+        variants_docs.push(
+            v_attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        let v_doc = doc_string(&v_attrs);
         all_flags.push(v_ident.clone());
         all_flags_names.push(quote::quote!(stringify!(#v_ident)));
+        all_flags_cfgs.push(v_cfg.clone());
+        all_flags_inverted.push(false);
+        all_flags_composite.push(flag_config.composite);
+        all_flags_docs.push(v_doc.clone());
 
         let expr = if let Some((_, expr)) = v.discriminant.as_ref() {
             quote::quote!(#expr)
         } else {
-            let expr = quote::quote!(1 << #i);
-            i += 1;
+            let msg = format!("flag `{}` exceeds the width of `{}`", v_ident, quote::quote!(#typ));
+            let expr = typ.bit(&shift, &msg, v_span);
+            shift.extend(quote::quote!(+ #v_count));
             expr
         };
 
-        let i_flag = config
-            .inverted_flags
-            .then(|| {
-                let i_ident = Ident::new(&format!("Inverted{}", v_ident), v_ident.span());
+        flag_checks.push(if flag_config.composite {
+            quote::quote_spanned!(v_span=>
+                #v_cfg
+                {
+                    let _ = #ident::#v_ident;
+                }
+            )
+        } else {
+            let zero_msg = format!(
+                "flag `{}` is zero, mark it with `#[bitmask_flag(composite)]` if this is intended",
+                v_ident,
+            );
+            let overlap_msg = format!(
+                "flag `{}` shares bits with another flag, mark it with `#[bitmask_flag(composite)]` if this is intended",
+                v_ident,
+            );
+            quote::quote_spanned!(v_span=>
+                #v_cfg
+                {
+                    let flag = #ident::#v_ident;
+                    assert!(!flag.is_none(), #zero_msg);
+                    assert!(flag.and(seen).is_none(), #overlap_msg);
+                    seen = seen.or(flag);
+                }
+            )
+        });
+
+        let i_flag = config
+            .inverted_flags
+            .then(|| {
+                let i_ident = Ident::new(&format!("{}{}", config.inverted_prefix, v_ident), v_ident.span());
+
+                flags_amount.extend(quote::quote!(+ #v_count));
+
+                all_flags.push(i_ident.clone());
+                all_flags_names.push(quote::quote!(stringify!(#i_ident)));
+                all_flags_cfgs.push(v_cfg.clone());
+                all_flags_inverted.push(true);
+                all_flags_composite.push(flag_config.composite);
+                all_flags_docs.push(v_doc.clone());
+
+                inverted_amount.extend(quote::quote!(+ #v_count));
+                inverted.push(i_ident.clone());
+                inverted_names.push(quote::quote!(stringify!(#i_ident)));
+                inverted_cfgs.push(v_cfg.clone());
+
+                quote::quote!(
+                    #(#v_attrs)*
+                    #vis const #i_ident: #ident = Self { bits: #expr }.not();
+                )
+            })
+            .into_iter();
+
+        flags.push(quote::quote!(
+            #(#v_attrs)*
+            #vis const #v_ident: #ident = Self { bits: #expr };
+
+            #(#i_flag)*
+        ))
+    }
+
+    let variants_names = variants
+        .iter()
+        .map(|v_ident| quote::quote!(stringify!(#v_ident)))
+        .collect::<Vec<_>>();
+    let variants_table = flags_table(&ident, &variants_names, &variants, &variants_cfgs, &variants_amount);
+
+    let flags_iter = config.flags_iter.then(|| {
+        let inverted_flags_iter = config.inverted_flags.then(|| {
+            let inverted_table = flags_table(&ident, &inverted_names, &inverted, &inverted_cfgs, &inverted_amount);
+            quote::quote!(
+                /// Returns an iterator over all inverted flags of the bitmask.
+                /// Where each Item = (name, flag).
+                #vis fn inverted_flags() -> impl core::iter::Iterator<Item = &'static (&'static str, Self)> {
+                    static FLAGS: [(&'static str, #ident); #inverted_amount] = #inverted_table;
+
+                    FLAGS.iter()
+                }
+            )
+        });
+
+        quote::quote!(
+            /// Returns an iterator over all flags of the bitmask,
+            /// not including inverted flags.
+            /// Where each Item = (name, flag).
+            #vis fn flags() -> impl core::iter::Iterator<Item = &'static (&'static str, Self)> {
+                static FLAGS: [(&'static str, #ident); #variants_amount] = #variants_table;
+
+                FLAGS.iter()
+            }
+
+            #inverted_flags_iter
+        )
+    }).into_iter();
+
+    let flag_info_ident = Ident::new(&format!("{}FlagInfo", ident), ident.span());
+
+    let single_bit = typ.count_ones(quote::quote!(value.bits));
+    let bit_index = typ.trailing_zeros(quote::quote!(value.bits));
+
+    let flag_info_impl = quote::quote! {
+        /// Information about a flag of a
+        #[doc = concat!("[`", stringify!(#ident), "`],")]
+        /// as listed in
+        #[doc = concat!("[`", stringify!(#ident), "::FLAG_INFO`].")]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #flag_info_ident {
+            name: &'static str,
+            value: #ident,
+            bit_index: Option<usize>,
+            composite: bool,
+            inverted: bool,
+            doc: &'static str,
+        }
+
+        impl #flag_info_ident {
+            const fn new(
+                name: &'static str,
+                value: #ident,
+                composite: bool,
+                inverted: bool,
+                doc: &'static str,
+            ) -> Self {
+                let bit_index = if #single_bit == 1 {
+                    Some(#bit_index)
+                } else {
+                    None
+                };
+                // Zero flags have to be marked as well, but do not combine any flags.
+                let composite = composite && #single_bit != 0;
+                Self { name, value, bit_index, composite, inverted, doc }
+            }
+
+            /// Returns the name of the flag.
+            #[inline]
+            #vis const fn name(&self) -> &'static str {
+                self.name
+            }
+
+            /// Returns the value of the flag.
+            #[inline]
+            #vis const fn value(&self) -> #ident {
+                self.value
+            }
+
+            /// Returns the index of the bit of the flag,
+            /// or `None` if the flag does not consist of exactly one bit.
+            #[inline]
+            #vis const fn bit_index(&self) -> Option<usize> {
+                self.bit_index
+            }
+
+            /// Returns `true` if the flag is marked with `#[bitmask_flag(composite)]`
+            /// and is not zero.
+            ///
+            /// Inverted flags are composite if the flag they invert is.
+            /// Use `.bit_index()` to check if a flag consists of exactly one bit.
+            #[inline]
+            #vis const fn is_composite(&self) -> bool {
+                self.composite
+            }
+
+            /// Returns `true` if the flag is the inverted version of another flag.
+            #[inline]
+            #vis const fn is_inverted(&self) -> bool {
+                self.inverted
+            }
+
+            /// Returns the doc comment of the flag, or an empty string if there is none.
+            #[inline]
+            #vis const fn doc(&self) -> &'static str {
+                self.doc
+            }
+        }
+
+        // Compares the bits, as the bitmask may not implement `Eq`.
+        impl PartialEq for #flag_info_ident {
+            fn eq(&self, other: &Self) -> bool {
+                self.name == other.name
+                    && self.value.bits == other.value.bits
+                    && self.bit_index == other.bit_index
+                    && self.composite == other.composite
+                    && self.inverted == other.inverted
+                    && self.doc == other.doc
+            }
+        }
+
+        impl Eq for #flag_info_ident {}
+    };
+
+    let from_bits_error_ident = Ident::new(&format!("{}FromBitsError", ident), ident.span());
+
+    let from_bits_error_impl = quote::quote! {
+        /// The error returned when converting bits that do not correspond to flags of a
+        #[doc = concat!("[`", stringify!(#ident), "`].")]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #vis struct #from_bits_error_ident {
+            bits: #typ,
+        }
+
+        impl #from_bits_error_ident {
+            /// Returns the bits that were rejected.
+            #[inline]
+            #vis const fn bits(&self) -> #typ {
+                self.bits
+            }
+
+            /// Returns the bits that do not correspond to any flag.
+            #[inline]
+            #vis const fn unknown_bits(&self) -> #typ {
+                #ident::from_bits_retain(self.bits).without(#ident::all_flags()).bits
+            }
+        }
+
+        impl core::fmt::Display for #from_bits_error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(
+                    f,
+                    "unknown bits {:#x} for {} in {:#x}",
+                    #ident::from_bits_retain(self.unknown_bits()),
+                    stringify!(#ident),
+                    #ident::from_bits_retain(self.bits),
+                )
+            }
+        }
+
+        impl core::error::Error for #from_bits_error_ident {}
+    };
+
+    let parse_error_ident = Ident::new(&format!("{}ParseError", ident), ident.span());
+
+    let parse_radix = typ.parse_radix(quote::quote!(digits), quote::quote!(radix));
+
+    let parse_impl = quote::quote! {
+        /// The error returned when parsing a
+        #[doc = concat!("[`", stringify!(#ident), "`]")]
+        /// from a string fails.
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #vis struct #parse_error_ident {
+            start: usize,
+            end: usize,
+        }
+
+        impl #parse_error_ident {
+            /// Returns the byte range of the token that failed to parse.
+            ///
+            /// The range is empty if a flag was expected but the token was empty.
+            #[inline]
+            #vis fn span(&self) -> core::ops::Range<usize> {
+                self.start..self.end
+            }
+
+            /// Returns the token of `input` that failed to parse.
+            #[inline]
+            #vis fn token<'a>(&self, input: &'a str) -> &'a str {
+                &input[self.start..self.end]
+            }
+        }
+
+        impl core::fmt::Display for #parse_error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                if self.start == self.end {
+                    write!(f, "expected flag of {} at byte {}", stringify!(#ident), self.start)
+                } else {
+                    write!(
+                        f,
+                        "unknown flag of {} at bytes {}..{}",
+                        stringify!(#ident),
+                        self.start,
+                        self.end,
+                    )
+                }
+            }
+        }
+
+        impl core::error::Error for #parse_error_ident {}
+
+        impl core::str::FromStr for #ident {
+            type Err = #parse_error_ident;
+
+            /// Parses flag names separated by `|`, numeric values are accepted as well.
+            #[inline]
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::from_str_with(s, &['|'], true)
+            }
+        }
+    };
+
+    let iter_ident = Ident::new(&format!("{}Iter", ident), ident.span());
+    let iter_names_ident = Ident::new(&format!("{}IterNames", ident), ident.span());
+
+    let bits_amount = typ.bits_amount();
+    let single_bit = typ.count_ones(quote::quote!(bits));
+    let single_bit_idx = typ.trailing_zeros(quote::quote!(bits));
+    let pop_lowest = typ.pop_lowest(quote::quote!(self.bits));
+    let flag_count_ones = typ.count_ones(quote::quote!(flag.1.bits));
+
+    let iter_impl = quote::quote! {
+        /// An iterator over the flags contained in a
+        #[doc = concat!("[`", stringify!(#ident), "`].")]
+        ///
+        #[doc = concat!("Returned by [`", stringify!(#ident), "::iter`].")]
+        #[derive(Clone, Debug)]
+        #vis struct #iter_ident {
+            inner: #iter_names_ident,
+        }
+
+        impl core::iter::Iterator for #iter_ident {
+            type Item = #ident;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map(|(_, flag)| flag)
+            }
+        }
+
+        /// An iterator over the names and flags contained in a
+        #[doc = concat!("[`", stringify!(#ident), "`].")]
+        ///
+        #[doc = concat!("Returned by [`", stringify!(#ident), "::iter_names`].")]
+        #[derive(Clone, Debug)]
+        #vis struct #iter_names_ident {
+            mask: #ident,
+            bits: #typ,
+            idx: usize,
+        }
+
+        impl #iter_names_ident {
+            const FLAGS: [(&'static str, #ident); #variants_amount] = #variants_table;
+
+            // Maps every bit index to the first single-bit flag with that bit,
+            // `usize::MAX` marks bits without any single-bit flag.
+            const SINGLE_BITS: [usize; #bits_amount] = {
+                let mut table = [usize::MAX; #bits_amount];
+                let mut i = 0;
+                while i < Self::FLAGS.len() {
+                    let bits = Self::FLAGS[i].1.bits;
+                    if #single_bit == 1 {
+                        let idx = #single_bit_idx;
+                        if table[idx] == usize::MAX {
+                            table[idx] = i;
+                        }
+                    }
+                    i += 1;
+                }
+                table
+            };
+        }
+
+        impl core::iter::Iterator for #iter_names_ident {
+            type Item = (&'static str, #ident);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                loop {
+                    let idx = #pop_lowest;
+                    let Some(idx) = idx else {
+                        break;
+                    };
+
+                    if let Some(flag) = Self::FLAGS.get(Self::SINGLE_BITS[idx]) {
+                        return Some(*flag);
+                    }
+                }
+
+                while let Some(flag) = Self::FLAGS.get(self.idx) {
+                    self.idx += 1;
+
+                    if #flag_count_ones > 1 && self.mask.contains(flag.1) {
+                        return Some(*flag);
+                    }
+                }
+
+                None
+            }
+        }
+
+        impl core::iter::IntoIterator for #ident {
+            type Item = #ident;
+            type IntoIter = #iter_ident;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl core::iter::IntoIterator for &#ident {
+            type Item = #ident;
+            type IntoIter = #iter_ident;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+    };
+
+    let display_impl = config.display.then(|| {
+        quote::quote! {
+            impl core::fmt::Display for #ident {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    if self.is_none() {
+                        return write!(f, "(empty)");
+                    }
+
+                    let mut unknown = *self;
+                    let mut has_flags = false;
+                    for (name, flag) in self.iter_names() {
+                        if has_flags {
+                            write!(f, " | {}", name)?;
+                        } else {
+                            write!(f, "{}", name)?;
+                            has_flags = true;
+                        }
+                        unknown.remove(flag);
+                    }
+
+                    if !unknown.is_none() {
+                        if has_flags {
+                            write!(f, " | {:#x}", unknown)?;
+                        } else {
+                            write!(f, "{:#x}", unknown)?;
+                        }
+                    }
+
+                    Ok(())
+                }
+            }
+        }
+    });
+
+    let flag_enum_impl = config.flag_enum.then(|| {
+        let flag_ident = Ident::new(&format!("{}Flag", ident), ident.span());
+
+        quote::quote! {
+            /// A single flag of a
+            #[doc = concat!("[`", stringify!(#ident), "`],")]
+            /// with one variant per declared flag.
+            #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+            #vis enum #flag_ident {
+                #(
+                    #variants_cfgs
+                    #(#variants_docs)*
+                    #variants,
+                )*
+            }
+
+            impl #flag_ident {
+                /// Returns the name of the flag.
+                #vis const fn name(&self) -> &'static str {
+                    match *self {
+                        #(#variants_cfgs Self::#variants => #variants_names,)*
+                    }
+                }
+            }
+
+            impl From<#flag_ident> for #ident {
+                #[inline]
+                fn from(flag: #flag_ident) -> Self {
+                    match flag {
+                        #(#variants_cfgs #flag_ident::#variants => Self::#variants,)*
+                    }
+                }
+            }
+
+            impl TryFrom<#ident> for #flag_ident {
+                type Error = #ident;
+
+                /// Returns the first declared flag equal to the bitmask,
+                /// or the bitmask itself as the error if there is none.
+                fn try_from(bm: #ident) -> Result<Self, Self::Error> {
+                    #(
+                        #variants_cfgs
+                        if bm == #ident::#variants {
+                            return Ok(Self::#variants);
+                        }
+                    )*
+                    Err(bm)
+                }
+            }
+
+            impl core::iter::FromIterator<#flag_ident> for #ident {
+                #[inline]
+                fn from_iter<I: core::iter::IntoIterator<Item = #flag_ident>>(iter: I) -> Self {
+                    iter.into_iter().map(Self::from).collect()
+                }
+            }
+
+            impl<'a> core::iter::FromIterator<&'a #flag_ident> for #ident {
+                #[inline]
+                fn from_iter<I: core::iter::IntoIterator<Item = &'a #flag_ident>>(iter: I) -> Self {
+                    iter.into_iter().copied().collect()
+                }
+            }
+
+            impl core::iter::Extend<#flag_ident> for #ident {
+                #[inline]
+                fn extend<I: core::iter::IntoIterator<Item = #flag_ident>>(&mut self, iter: I) {
+                    self.extend(iter.into_iter().map(Self::from));
+                }
+            }
+
+            impl core::iter::Sum<#flag_ident> for #ident {
+                #[inline]
+                fn sum<I: core::iter::Iterator<Item = #flag_ident>>(iter: I) -> Self {
+                    iter.collect()
+                }
+            }
+
+            impl #ident {
+                /// Returns an iterator over the flags contained in the bitmask,
+                #[doc = concat!("as [`", stringify!(#flag_ident), "`] items.")]
+                ///
+                /// The flags are yielded in the same order as `.iter()`.
+                #[inline]
+                #vis fn iter_flags(&self) -> impl core::iter::Iterator<Item = #flag_ident> {
+                    self.iter().filter_map(|flag| #flag_ident::try_from(flag).ok())
+                }
+            }
+        }
+    });
+
+    let atomic_impl = if config.atomic {
+        let (atomic, width) = typ.atomic()?;
+        let atomic_ident = Ident::new(&format!("Atomic{}", ident), ident.span());
+
+        Some(quote::quote! {
+            /// A
+            #[doc = concat!("[`", stringify!(#ident), "`]")]
+            /// which can be safely shared between threads.
+            #[cfg(target_has_atomic = #width)]
+            #[repr(transparent)]
+            #vis struct #atomic_ident {
+                bits: core::sync::atomic::#atomic,
+            }
+
+            #[cfg(target_has_atomic = #width)]
+            impl #atomic_ident {
+                /// Creates a new atomic bitmask.
+                #[inline]
+                #vis const fn new(bm: #ident) -> Self {
+                    Self { bits: core::sync::atomic::#atomic::new(bm.bits) }
+                }
+
+                /// Consumes the atomic and returns the contained bitmask.
+                #[inline]
+                #vis fn into_inner(self) -> #ident {
+                    #ident { bits: self.bits.into_inner() }
+                }
+
+                /// Loads the bitmask.
+                #[inline]
+                #vis fn load(&self, order: core::sync::atomic::Ordering) -> #ident {
+                    #ident { bits: self.bits.load(order) }
+                }
+
+                /// Stores `bm` into the atomic.
+                #[inline]
+                #vis fn store(&self, bm: #ident, order: core::sync::atomic::Ordering) {
+                    self.bits.store(bm.bits, order)
+                }
+
+                /// Stores `bm` into the atomic, returning the previous bitmask.
+                #[inline]
+                #vis fn swap(&self, bm: #ident, order: core::sync::atomic::Ordering) -> #ident {
+                    #ident { bits: self.bits.swap(bm.bits, order) }
+                }
+
+                /// Adds all values of `other`, returning the previous bitmask.
+                #[inline]
+                #vis fn fetch_insert(&self, other: #ident, order: core::sync::atomic::Ordering) -> #ident {
+                    #ident { bits: self.bits.fetch_or(other.bits, order) }
+                }
+
+                /// Removes all values of `other`, returning the previous bitmask.
+                #[inline]
+                #vis fn fetch_remove(&self, other: #ident, order: core::sync::atomic::Ordering) -> #ident {
+                    #ident { bits: self.bits.fetch_and(!other.bits, order) }
+                }
+
+                /// Toggles all values of `other`, returning the previous bitmask.
+                #[inline]
+                #vis fn fetch_toggle(&self, other: #ident, order: core::sync::atomic::Ordering) -> #ident {
+                    #ident { bits: self.bits.fetch_xor(other.bits, order) }
+                }
+
+                /// Stores `new` if the current bitmask is equal to `current`.
+                ///
+                /// Returns `Ok` with the previous bitmask if it was stored, `Err` with the current one otherwise.
+                #[inline]
+                #vis fn compare_exchange(
+                    &self,
+                    current: #ident,
+                    new: #ident,
+                    success: core::sync::atomic::Ordering,
+                    failure: core::sync::atomic::Ordering,
+                ) -> Result<#ident, #ident> {
+                    self.bits
+                        .compare_exchange(current.bits, new.bits, success, failure)
+                        .map(|bits| #ident { bits })
+                        .map_err(|bits| #ident { bits })
+                }
+
+                /// Stores the bitmask returned by `f` until it succeeds or `f` returns `None`.
+                ///
+                /// Returns `Ok` with the previous bitmask if a new one was stored, `Err` with the current one otherwise.
+                #[inline]
+                #vis fn fetch_update<F>(
+                    &self,
+                    set_order: core::sync::atomic::Ordering,
+                    fetch_order: core::sync::atomic::Ordering,
+                    mut f: F,
+                ) -> Result<#ident, #ident>
+                where
+                    F: FnMut(#ident) -> Option<#ident>,
+                {
+                    self.bits
+                        .fetch_update(set_order, fetch_order, |bits| f(#ident { bits }).map(|bm| bm.bits))
+                        .map(|bits| #ident { bits })
+                        .map_err(|bits| #ident { bits })
+                }
+            }
+
+            #[cfg(target_has_atomic = #width)]
+            impl Default for #atomic_ident {
+                #[inline]
+                fn default() -> Self {
+                    Self::new(#ident::none())
+                }
+            }
+
+            #[cfg(target_has_atomic = #width)]
+            impl From<#ident> for #atomic_ident {
+                #[inline]
+                fn from(bm: #ident) -> Self {
+                    Self::new(bm)
+                }
+            }
+
+            #[cfg(target_has_atomic = #width)]
+            impl core::fmt::Debug for #atomic_ident {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    core::fmt::Debug::fmt(&self.load(core::sync::atomic::Ordering::Relaxed), f)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let serde_impl = config.serde.map(|unknown_bits| {
+        let unknown_bits = match unknown_bits {
+            UnknownBits::Reject => quote::quote! {
+                #ident::try_from_bits(bm.bits).map_err(::serde::de::Error::custom)
+            },
+            UnknownBits::Truncate => quote::quote!(Ok(bm.truncate())),
+            UnknownBits::Retain => quote::quote!(Ok(bm)),
+        };
+
+        let serialize_bits = typ.serialize(quote::quote!(self.bits), quote::quote!(serializer));
+        let deserialize_bits = typ.deserialize(quote::quote!(deserializer));
+
+        quote::quote! {
+            impl ::serde::Serialize for #ident {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: ::serde::Serializer,
+                {
+                    if !serializer.is_human_readable() {
+                        return #serialize_bits;
+                    }
+
+                    let mut len = 0;
+                    let mut unknown = *self;
+                    for flag in self.iter() {
+                        len += 1;
+                        unknown.remove(flag);
+                    }
+                    if !unknown.is_none() {
+                        len += 1;
+                    }
+
+                    let mut seq = ::serde::Serializer::serialize_seq(serializer, Some(len))?;
+                    for (name, _) in self.iter_names() {
+                        ::serde::ser::SerializeSeq::serialize_element(&mut seq, name)?;
+                    }
+                    if !unknown.is_none() {
+                        ::serde::ser::SerializeSeq::serialize_element(
+                            &mut seq,
+                            &format_args!("{:#x}", unknown),
+                        )?;
+                    }
+                    ::serde::ser::SerializeSeq::end(seq)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #ident {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: ::serde::Deserializer<'de>,
+                {
+                    struct FlagVisitor;
+
+                    impl<'de> ::serde::de::Visitor<'de> for FlagVisitor {
+                        type Value = #ident;
+
+                        fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            write!(f, "a flag name of {}", stringify!(#ident))
+                        }
+
+                        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                        where
+                            E: ::serde::de::Error,
+                        {
+                            #ident::from_str_with(v, &[], true).map_err(|_| {
+                                ::serde::de::Error::invalid_value(::serde::de::Unexpected::Str(v), &self)
+                            })
+                        }
+                    }
+
+                    impl<'de> ::serde::de::DeserializeSeed<'de> for FlagVisitor {
+                        type Value = #ident;
+
+                        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: ::serde::Deserializer<'de>,
+                        {
+                            ::serde::Deserializer::deserialize_str(deserializer, self)
+                        }
+                    }
+
+                    struct FlagsVisitor;
+
+                    impl<'de> ::serde::de::Visitor<'de> for FlagsVisitor {
+                        type Value = #ident;
+
+                        fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            write!(f, "a sequence of flag names of {}", stringify!(#ident))
+                        }
+
+                        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                        where
+                            A: ::serde::de::SeqAccess<'de>,
+                        {
+                            let mut bm = #ident::none();
+                            while let Some(flag) = seq.next_element_seed(FlagVisitor)? {
+                                bm.insert(flag);
+                            }
+                            Ok(bm)
+                        }
+                    }
+
+                    let bm = if ::serde::Deserializer::is_human_readable(&deserializer) {
+                        ::serde::Deserializer::deserialize_seq(deserializer, FlagsVisitor)?
+                    } else {
+                        #ident::from_bits_retain(#deserialize_bits?)
+                    };
+
+                    #unknown_bits
+                }
+            }
+        }
+    });
+
+    let debug_flags_impl = (config.vec_debug || config.defmt || config.ufmt).then(|| {
+        let count_ones = typ.count_ones(quote::quote!(flag.bits));
+        quote::quote! {
+            impl #ident {
+                /// The flags in declaration order, as listed by the debug formatting.
+                const DEBUG_FLAGS: [(&'static str, #ident); #variants_amount] = #variants_table;
+
+                /// Returns which of `DEBUG_FLAGS` are listed by the debug formatting,
+                /// together with the remaining bits.
+                ///
+                /// The largest matching flags are listed,
+                /// skipping flags that are already covered by other listed flags.
+                fn debug_flags(&self) -> ([bool; #variants_amount], Self) {
+                    // The indices into `DEBUG_FLAGS`, ordered by descending amount of bits,
+                    // flags with the same amount stay in declaration order.
+                    const ORDER: [usize; #variants_amount] = {
+                        const fn count_ones(flag: #ident) -> u32 {
+                            #count_ones
+                        }
+
+                        let flags = #ident::DEBUG_FLAGS;
+                        let mut order = [0; #variants_amount];
+                        let mut i = 0;
+                        while i < order.len() {
+                            order[i] = i;
+                            i += 1;
+                        }
+
+                        let mut i = 1;
+                        while i < order.len() {
+                            let mut j = i;
+                            while j > 0
+                                && count_ones(flags[order[j - 1]].1) < count_ones(flags[order[j]].1)
+                            {
+                                let tmp = order[j - 1];
+                                order[j - 1] = order[j];
+                                order[j] = tmp;
+                                j -= 1;
+                            }
+                            i += 1;
+                        }
+                        order
+                    };
+
+                    let mut shown = Self::none();
+                    let mut selected = [false; #variants_amount];
+                    for &idx in ORDER.iter() {
+                        let flag = Self::DEBUG_FLAGS[idx].1;
+                        if !flag.is_none() && self.contains(flag) && !shown.contains(flag) {
+                            selected[idx] = true;
+                            shown = shown.or(flag);
+                        }
+                    }
+                    (selected, self.without(shown))
+                }
+            }
+        }
+    });
+
+    let defmt_impl = config.defmt.then(|| {
+        let ident_str = ident.to_string();
+        let variants_strs = variants.iter().map(|v_ident| v_ident.to_string());
+        let unknown_bits = match typ.unsigned() {
+            Some(_) => quote::quote!(rest.unsigned_bits()),
+            None => quote::quote!(rest.bits),
+        };
+        quote::quote! {
+            impl ::defmt::Format for #ident {
+                fn format(&self, f: ::defmt::Formatter) {
+                    let (selected, rest) = self.debug_flags();
+
+                    ::defmt::write!(f, "{=istr}[", ::defmt::intern!(#ident_str));
+                    let mut has_flags = false;
+                    let mut i = 0;
+                    #(#variants_cfgs
+                    {
+                        if selected[i] {
+                            if has_flags {
+                                ::defmt::write!(f, ", ");
+                            }
+                            ::defmt::write!(f, "{=istr}", ::defmt::intern!(#variants_strs));
+                            has_flags = true;
+                        }
+                        i += 1;
+                    })*
+                    let _ = i;
+
+                    if !rest.is_none() {
+                        if has_flags {
+                            ::defmt::write!(f, ", ");
+                        }
+                        ::defmt::write!(f, "{:#x}", #unknown_bits);
+                    }
+                    ::defmt::write!(f, "]");
+                }
+            }
+        }
+    });
+
+    let ufmt_impl = config.ufmt.then(|| {
+        let nibble = typ.nibble(quote::quote!(bm.bits), quote::quote!(d));
+        quote::quote! {
+            const _: () = {
+                // Writes the bits as a `0x` prefixed hex number without leading zeros.
+                fn write_hex<W>(bm: #ident, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ::ufmt::uWrite + ?Sized,
+                {
+                    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+                    f.write_str("0x")?;
+                    let mut started = false;
+                    let mut d = #bits_amount / 4;
+                    while d > 0 {
+                        d -= 1;
+                        let digit = #nibble;
+                        if digit != 0 || started || d == 0 {
+                            f.write_char(DIGITS[digit] as char)?;
+                            started = true;
+                        }
+                    }
+                    Ok(())
+                }
+
+                impl ::ufmt::uDebug for #ident {
+                    fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::ufmt::uWrite + ?Sized,
+                    {
+                        let (selected, rest) = self.debug_flags();
+
+                        f.write_str(stringify!(#ident))?;
+                        f.write_str("[")?;
+                        let mut has_flags = false;
+                        for (&(name, _), &selected) in Self::DEBUG_FLAGS.iter().zip(selected.iter()) {
+                            if selected {
+                                if has_flags {
+                                    f.write_str(", ")?;
+                                }
+                                f.write_str(name)?;
+                                has_flags = true;
+                            }
+                        }
+
+                        if !rest.is_none() {
+                            if has_flags {
+                                f.write_str(", ")?;
+                            }
+                            write_hex(rest, f)?;
+                        }
+                        f.write_str("]")
+                    }
+                }
+
+                impl ::ufmt::uDisplay for #ident {
+                    fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::ufmt::uWrite + ?Sized,
+                    {
+                        if self.is_none() {
+                            return f.write_str("(empty)");
+                        }
+
+                        let mut unknown = *self;
+                        let mut has_flags = false;
+                        for (name, flag) in self.iter_names() {
+                            if has_flags {
+                                f.write_str(" | ")?;
+                            }
+                            f.write_str(name)?;
+                            has_flags = true;
+                            unknown.remove(flag);
+                        }
+
+                        if !unknown.is_none() {
+                            if has_flags {
+                                f.write_str(" | ")?;
+                            }
+                            write_hex(unknown, f)?;
+                        }
+                        Ok(())
+                    }
+                }
+            };
+        }
+    });
+
+    // Both pick every declared flag with a chance of 1/2,
+    // adding random bits with the configured chance.
+    let arbitrary_impl = config.arbitrary.map(|chance| {
+        // `Unstructured::ratio` panics on a zero chance
+        let unknown = (chance > 0).then(|| {
+            quote::quote! {
+                if u.ratio(#chance, 100)? {
+                    bm.insert(Self::from_bits_retain(u.arbitrary()?));
+                }
+            }
+        });
+        quote::quote! {
+            impl<'a> ::arbitrary::Arbitrary<'a> for #ident {
+                fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                    const FLAGS: [(&str, #ident); #variants_amount] = #variants_table;
+
+                    let mut bm = Self::none();
+                    for &(_, flag) in FLAGS.iter() {
+                        if u.arbitrary()? {
+                            bm.insert(flag);
+                        }
+                    }
+                    #unknown
+                    Ok(bm)
+                }
+            }
+        }
+    });
+
+    let proptest_impl = config.proptest.map(|chance| {
+        let weight = chance as f64 / 100.0;
+        quote::quote! {
+            impl ::proptest::arbitrary::Arbitrary for #ident {
+                type Parameters = ();
+                type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    use ::proptest::strategy::Strategy;
+
+                    const FLAGS: [(&str, #ident); #variants_amount] = #variants_table;
+
+                    (
+                        ::proptest::collection::vec(::proptest::bool::ANY, FLAGS.len()),
+                        ::proptest::bool::weighted(#weight),
+                        ::proptest::arbitrary::any::<#typ>(),
+                    )
+                        .prop_map(|(selected, unknown, bits)| {
+                            let mut bm = Self::none();
+                            for (&(_, flag), selected) in FLAGS.iter().zip(selected) {
+                                if selected {
+                                    bm.insert(flag);
+                                }
+                            }
+                            if unknown {
+                                bm.insert(Self::from_bits_retain(bits));
+                            }
+                            bm
+                        })
+                        .boxed()
+                }
+            }
+        }
+    });
+
+    let schemars_impl = config.schemars.map(|format| {
+        let (generator, schema) = match format {
+            SchemaFormat::Names => {
+                // Undeclared bits are serialized as a hex number, which strict bitmasks never contain.
+                let (unknown_amount, unknown_schema) = if config.strict {
+                    (quote::quote!(0), None)
+                } else {
+                    (
+                        quote::quote!(1),
+                        Some(quote::quote! {
+                            ::schemars::json_schema!({
+                                "pattern": "^0x[0-9a-f]+$",
+                                "description": "Undeclared bits as a hex number.",
+                            })
+                        }),
+                    )
+                };
+
+                // Flags are only listed separately if they have a description.
+                let items = if variants_docs.iter().all(|docs| docs.is_empty()) {
+                    let names_table = quote::quote!({
+                        let mut table = [""; #variants_amount];
+                        let mut i = 0;
+                        #(#variants_cfgs
+                        {
+                            table[i] = #variants_names;
+                            i += 1;
+                        })*
+                        let _ = i;
+                        table
+                    });
+                    let names_schema = quote::quote! {
+                        ::schemars::json_schema!({ "enum": &NAMES[..] })
+                    };
+                    match unknown_schema {
+                        Some(unknown_schema) => quote::quote! {
+                            const NAMES: [&str; #variants_amount] = #names_table;
+                            ::schemars::json_schema!({
+                                "type": "string",
+                                "oneOf": [#names_schema, #unknown_schema],
+                            })
+                        },
+                        None => quote::quote! {
+                            const NAMES: [&str; #variants_amount] = #names_table;
+                            ::schemars::json_schema!({ "type": "string", "enum": &NAMES[..] })
+                        },
+                    }
+                } else {
+                    let flag_schemas = variants.iter().zip(&variants_docs).map(|(v_ident, docs)| {
+                        if docs.is_empty() {
+                            quote::quote!(::schemars::json_schema!({ "const": stringify!(#v_ident) }))
+                        } else {
+                            let doc = doc_string(docs);
+                            quote::quote! {
+                                ::schemars::json_schema!({
+                                    "const": stringify!(#v_ident),
+                                    "description": #doc,
+                                })
+                            }
+                        }
+                    });
+                    let unknown_schema = unknown_schema.map(|unknown_schema| {
+                        quote::quote!(schemas[i] = #unknown_schema;)
+                    });
+                    quote::quote! {
+                        let mut schemas: [::schemars::Schema; #variants_amount + #unknown_amount] =
+                            core::array::from_fn(|_| ::schemars::Schema::default());
+                        let mut i = 0;
+                        #(#variants_cfgs
+                        {
+                            schemas[i] = #flag_schemas;
+                            i += 1;
+                        })*
+                        #unknown_schema
+                        let _ = i;
+                        ::schemars::json_schema!({ "type": "string", "oneOf": &schemas[..] })
+                    }
+                };
+
+                (quote::quote!(_), quote::quote! {
+                    let items = { #items };
+                    ::schemars::json_schema!({
+                        "type": "array",
+                        "items": items,
+                        "uniqueItems": true,
+                    })
+                })
+            }
+            SchemaFormat::Bits => (quote::quote!(generator), quote::quote! {
+                struct FlagValues;
 
-                all_flags.push(i_ident.clone());
-                all_flags_names.push(quote::quote!(stringify!(#i_ident)));
+                impl core::fmt::Display for FlagValues {
+                    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                        const FLAGS: [(&str, #ident); #variants_amount] = #variants_table;
 
-                quote::quote!(
-                    #(#v_attrs)*
-                    #vis const #i_ident: #ident = Self { bits: (#expr) ^ !0 };
-                )
-            })
-            .into_iter();
+                        for (i, (name, flag)) in FLAGS.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "{} = {:#x}", name, flag)?;
+                        }
+                        Ok(())
+                    }
+                }
 
-        flags.push(quote::quote!(
-            #(#v_attrs)*
-            #vis const #v_ident: #ident = Self { bits: #expr };
+                let mut schema = generator.subschema_for::<#typ>();
+                schema.insert(
+                    "description".into(),
+                    alloc::format!("A bitmask of the flags {}", FlagValues).into(),
+                );
+                schema
+            }),
+        };
 
-            #(#i_flag)*
-        ))
-    }
+        quote::quote! {
+            const _: () = {
+                // `Cow` is only available from `alloc`, which `schemars` depends on anyway.
+                extern crate alloc;
 
-    let flags_iter = config.flags_iter.then(|| {
-        quote::quote!(
-            /// Returns an iterator over all flags of the bitmask.
-            /// Where each Item = (name, flag).
-            #vis fn flags() -> impl core::iter::Iterator<Item = &'static (&'static str, Self)> {
-                static FLAGS: [(&'static str, #ident); #flags_amount] = [
-                    #((#all_flags_names, #ident::#all_flags),)*
-                ];
+                impl ::schemars::JsonSchema for #ident {
+                    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+                        stringify!(#ident).into()
+                    }
 
-                FLAGS.iter()
-            }
-        )
-    }).into_iter();
+                    fn schema_id() -> alloc::borrow::Cow<'static, str> {
+                        concat!(module_path!(), "::", stringify!(#ident)).into()
+                    }
+
+                    fn json_schema(#generator: &mut ::schemars::SchemaGenerator) -> ::schemars::Schema {
+                        #schema
+                    }
+                }
+            };
+        }
+    });
 
     let debug_impl = if config.vec_debug {
         quote::quote! {
             impl core::fmt::Debug for #ident {
                 fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-                    write!(f, "{}[", stringify!(#ident))?;
+                    /// Lists the largest matching flags that are not already covered
+                    /// by other listed flags, followed by the remaining bits.
+                    struct FlagList(#ident);
 
-                    let mut has_flags = false;
-                    #(if self.contains(Self::#all_flags) {
-                        if has_flags {
-                            write!(f, ", {}", #all_flags_names)?;
-                        } else {
-                            write!(f, "{}", #all_flags_names)?;
-                            has_flags = true;
+                    impl core::fmt::Debug for FlagList {
+                        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                            let (selected, rest) = self.0.debug_flags();
+
+                            let mut list = f.debug_list();
+                            for (&(name, _), &selected) in #ident::DEBUG_FLAGS.iter().zip(selected.iter()) {
+                                if selected {
+                                    list.entry(&format_args!("{}", name));
+                                }
+                            }
+
+                            if !rest.is_none() {
+                                list.entry(&format_args!("{:#x}", rest));
+                            }
+                            list.finish()
                         }
-                    })*
+                    }
 
-                    write!(f, "]")
+                    if f.alternate() {
+                        f.debug_struct(stringify!(#ident))
+                            .field("flags", &FlagList(*self))
+                            .field("bits", &format_args!("{:#x}", self))
+                            .finish()
+                    } else {
+                        write!(f, "{}", stringify!(#ident))?;
+                        core::fmt::Debug::fmt(&FlagList(*self), f)
+                    }
                 }
             }
         }
     } else {
+        let debug_bits = match typ.unsigned() {
+            Some(unsigned) => quote::quote!((self.bits as #unsigned)),
+            None => quote::quote!(self.bits),
+        };
         quote::quote! {
             impl core::fmt::Debug for #ident {
                 fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
                     f.debug_struct(stringify!(#ident))
-                        .field("bits", &self.bits)
+                        .field("bits", &#debug_bits)
                         .finish()
                 }
             }
         }
     };
 
+    // Signed types are converted by their unsigned bit pattern,
+    // so a flag in the sign bit does not make a bitmask negative.
+    let signed_impl = typ.unsigned().map(|unsigned| {
+        // Unchecked like `::from_bits_retain()`, so there is none in strict mode.
+        let from_unsigned_bits = (!config.strict).then(|| quote::quote! {
+            /// Returns a bitmask with the given bits
+            #[doc = concat!("reinterpreted as `", stringify!(#typ), "`.")]
+            #[inline]
+            #vis const fn from_unsigned_bits(bits: #unsigned) -> Self {
+                Self { bits: bits as #typ }
+            }
+        });
+        // Mirrors the conversion from `#typ`.
+        let from_unsigned_impl = if config.strict {
+            quote::quote! {
+                impl TryFrom<#unsigned> for #ident {
+                    type Error = #from_bits_error_ident;
+
+                    #[inline]
+                    fn try_from(bits: #unsigned) -> Result<Self, Self::Error> {
+                        Self::try_from_bits(bits as #typ)
+                    }
+                }
+            }
+        } else {
+            quote::quote! {
+                impl From<#unsigned> for #ident {
+                    #[inline]
+                    fn from(val: #unsigned) -> Self {
+                        Self::from_unsigned_bits(val)
+                    }
+                }
+            }
+        };
+        quote::quote! {
+            impl #ident {
+                /// Returns the underlying bits of the bitmask
+                #[doc = concat!("reinterpreted as `", stringify!(#unsigned), "`.")]
+                #[inline]
+                #vis const fn unsigned_bits(&self) -> #unsigned {
+                    self.bits as #unsigned
+                }
+
+                #from_unsigned_bits
+            }
+
+            impl From<#ident> for #unsigned {
+                #[inline]
+                fn from(val: #ident) -> #unsigned {
+                    val.unsigned_bits()
+                }
+            }
+
+            #from_unsigned_impl
+        }
+    });
+
+    // Bitmasks are ordered by their value like their unsigned integer counterparts,
+    // which the derived ordering of signed and array bits does not do.
+    let cmp = typ.cmp(quote::quote!(self.bits), quote::quote!(other.bits));
+    let ord_derive = cmp.is_none();
+    let ord_impl = cmp.map(|cmp| {
+        let partial_ord_impl = config.derives("PartialOrd").then(|| quote::quote! {
+            impl PartialOrd for #ident {
+                #[inline]
+                fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                    Some(#cmp)
+                }
+            }
+        });
+        let ord_impl = config.derives("Ord").then(|| quote::quote! {
+            impl Ord for #ident {
+                #[inline]
+                fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                    #cmp
+                }
+            }
+        });
+        quote::quote! {
+            #partial_ord_impl
+
+            #ord_impl
+        }
+    });
+
+    let derives = DERIVES
+        .iter()
+        .filter(|name| config.derives(name) && (ord_derive || !matches!(**name, "PartialOrd" | "Ord")))
+        .map(|name| Ident::new(name, Span::call_site()).into_token_stream())
+        .chain(config.derive.iter().map(|path| path.into_token_stream()));
+
+    let default_impl = config.default.as_ref().map(|flags| {
+        quote::quote! {
+            impl Default for #ident {
+                #[inline]
+                fn default() -> Self {
+                    Self::none()#(.or(Self::#flags))*
+                }
+            }
+        }
+    });
+
+    // The struct is `#[repr(transparent)]` over integers without padding,
+    // so every bit pattern is valid outside of strict mode.
+    let bytemuck_impl = config.bytemuck.then(|| {
+        quote::quote! {
+            unsafe impl ::bytemuck::Zeroable for #ident {}
+
+            unsafe impl ::bytemuck::Pod for #ident {}
+        }
+    });
+
+    let zerocopy_derive = config.zerocopy.then(|| {
+        quote::quote! {
+            #[derive(
+                ::zerocopy::FromBytes,
+                ::zerocopy::IntoBytes,
+                ::zerocopy::Immutable,
+                ::zerocopy::KnownLayout,
+            )]
+        }
+    });
+
+    let zero = typ.zero();
+    let ones = typ.ones();
+    let not = typ.not(quote::quote!(self.bits));
+
+    // In strict mode the bits can never contain bits that do not correspond to a flag,
+    // so raw bits are only accepted through validating methods.
+    let (retain_vis, from_radix, all_bits_impl, not_impl, from_typ_impl) = if config.strict {
+        (
+            quote::quote!(),
+            quote::quote!(Self::from_bits),
+            quote::quote! {
+                /// Returns a bitmask that contains all values.
+                ///
+                /// This is equivalent to `::all_flags()` in strict mode.
+                #[inline]
+                #vis const fn all_bits() -> Self {
+                    Self::all_flags()
+                }
+            },
+            quote::quote! {
+                /// Returns the bitwise NOT of the bitmask,
+                /// only containing bits corresponding to flags in strict mode.
+                #[inline]
+                #vis const fn not(self) -> Self {
+                    Self { bits: #not }.truncate()
+                }
+            },
+            quote::quote! {
+                impl TryFrom<#typ> for #ident {
+                    type Error = #from_bits_error_ident;
+
+                    #[inline]
+                    fn try_from(bits: #typ) -> Result<Self, Self::Error> {
+                        Self::try_from_bits(bits)
+                    }
+                }
+            },
+        )
+    } else {
+        (
+            quote::quote!(#vis),
+            quote::quote!(|bits| Some(Self::from_bits_retain(bits))),
+            quote::quote! {
+                /// Returns a bitmask that contains all values.
+                ///
+                /// This will include bits that do not have any flags.
+                /// Use `::all_flags()` if you only want to use flags.
+                #[inline]
+                #vis const fn all_bits() -> Self {
+                    Self { bits: #ones }
+                }
+            },
+            quote::quote! {
+                /// Returns the bitwise NOT of the bitmask.
+                #[inline]
+                #vis const fn not(self) -> Self {
+                    Self { bits: #not }
+                }
+            },
+            quote::quote! {
+                impl From<#typ> for #ident {
+                    #[inline]
+                    fn from(val: #typ) -> Self {
+                        Self { bits: val }
+                    }
+                }
+            },
+        )
+    };
+
+    let is_all_bits = typ.eq(quote::quote!(self.bits), quote::quote!(Self::all_bits().bits));
+    let is_all_flags = typ.eq(quote::quote!(self.bits), quote::quote!(Self::all_flags().bits));
+    let is_none = typ.eq(quote::quote!(self.bits), typ.zero());
+    let contains = typ.eq(quote::quote!(self.and(other).bits), quote::quote!(other.bits));
+    let and = typ.binop(quote::quote!(self.bits), quote::quote!(&), quote::quote!(other.bits));
+    // Not expressed through `.not()`, which stays within the flags in strict mode.
+    let without = typ.binop(quote::quote!(self.bits), quote::quote!(&), typ.not(quote::quote!(other.bits)));
+    let or = typ.binop(quote::quote!(self.bits), quote::quote!(|), quote::quote!(other.bits));
+    let xor = typ.binop(quote::quote!(self.bits), quote::quote!(^), quote::quote!(other.bits));
+    let fmt_impls = typ.fmt_impls(&ident);
+
     Ok(TokenStream::from(quote::quote! {
         #(#attrs)*
         #[repr(transparent)]
-        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[derive(#(#derives),*)]
+        #zerocopy_derive
         #vis struct #ident {
             bits: #typ,
         }
 
+        // Rejects zero-valued and overlapping flags at compile time,
+        // implicit flags additionally check that they fit into the type.
+        #[allow(unused_mut, unused_variables, unused_assignments)]
+        const _: () = {
+            let mut seen = #ident::none();
+            #(#flag_checks)*
+        };
+
         #[allow(non_upper_case_globals)]
         impl #ident {
             #(#flags)*
 
+            /// The information about all flags of the bitmask, in declaration order.
+            #vis const FLAG_INFO: &'static [#flag_info_ident] = &{
+                let mut table = [#flag_info_ident::new("", Self::none(), false, false, ""); #flags_amount];
+                let mut i = 0;
+                #(#all_flags_cfgs
+                {
+                    table[i] = #flag_info_ident::new(
+                        #all_flags_names,
+                        Self::#all_flags,
+                        #all_flags_composite,
+                        #all_flags_inverted,
+                        #all_flags_docs,
+                    );
+                    i += 1;
+                })*
+                let _ = i;
+                table
+            };
+
+            /// The amount of flags of the bitmask, equal to `FLAG_INFO.len()`.
+            #vis const FLAG_COUNT: usize = #flags_amount;
+
+            /// The amount of bits of the bitmask.
+            #vis const BITS: u32 = #bits_amount as u32;
+
             #(#flags_iter)*
 
+            /// Returns the flag with the given name, or `None` if there is no such flag.
+            #vis fn from_name(name: &str) -> Option<Self> {
+                #(#all_flags_cfgs
+                if name == #all_flags_names {
+                    return Some(Self::#all_flags);
+                })*
+                None
+            }
+
+            /// Parses a bitmask from flag names separated by any of `separators`.
+            ///
+            /// Whitespace around each name is ignored,
+            /// a blank string or `(empty)` parses as `::none()`.
+            /// If `numeric` is `true`, tokens starting with a digit are parsed as raw bits,
+            /// with an optional `0x`, `0o` or `0b` prefix.
+            #vis fn from_str_with(
+                s: &str,
+                separators: &[char],
+                numeric: bool,
+            ) -> Result<Self, #parse_error_ident> {
+                if s.trim().is_empty() || s.trim() == "(empty)" {
+                    return Ok(Self::none());
+                }
+
+                let mut bm = Self::none();
+                for part in s.split(separators) {
+                    let token = part.trim();
+                    let start = token.as_ptr() as usize - s.as_ptr() as usize;
+                    let end = start + token.len();
+
+                    let flag = if numeric && token.starts_with(|c: char| c.is_ascii_digit()) {
+                        let (digits, radix) = if let Some(digits) = token.strip_prefix("0x") {
+                            (digits, 16)
+                        } else if let Some(digits) = token.strip_prefix("0o") {
+                            (digits, 8)
+                        } else if let Some(digits) = token.strip_prefix("0b") {
+                            (digits, 2)
+                        } else {
+                            (token, 10)
+                        };
+                        #parse_radix.and_then(#from_radix)
+                    } else {
+                        Self::from_name(token)
+                    };
+
+                    match flag {
+                        Some(flag) => bm.insert(flag),
+                        None => return Err(#parse_error_ident { start, end }),
+                    }
+                }
+                Ok(bm)
+            }
+
+            /// Returns an iterator over the flags contained in the bitmask.
+            ///
+            /// Single-bit flags are yielded first, in ascending bit order.
+            /// If multiple flags share the same bit, only the first declared one is yielded.
+            /// Composite flags are yielded afterwards, in declaration order,
+            /// if all of their bits are contained in the bitmask.
+            ///
+            /// Zero-valued flags, inverted flags and bits without a flag are never yielded.
+            #[inline]
+            #vis const fn iter(&self) -> #iter_ident {
+                #iter_ident { inner: self.iter_names() }
+            }
+
+            /// Returns an iterator over the names and flags contained in the bitmask.
+            /// Where each Item = (name, flag).
+            ///
+            /// The flags are yielded in the same order as `.iter()`.
+            #[inline]
+            #vis const fn iter_names(&self) -> #iter_names_ident {
+                #iter_names_ident { mask: *self, bits: self.bits, idx: 0 }
+            }
+
             /// Returns the underlying bits of the bitmask.
             #[inline]
             #vis const fn bits(&self) -> #typ {
                 self.bits
             }
 
-            /// Returns a bitmask that contains all values.
+            /// Returns a bitmask with the given bits,
+            /// or `None` if any bit does not correspond to a flag.
+            #[inline]
+            #vis const fn from_bits(bits: #typ) -> Option<Self> {
+                match Self::try_from_bits(bits) {
+                    Ok(bm) => Some(bm),
+                    Err(_) => None,
+                }
+            }
+
+            /// Returns a bitmask with the given bits,
+            /// or an error reporting the bits that do not correspond to a flag.
+            #[inline]
+            #vis const fn try_from_bits(bits: #typ) -> Result<Self, #from_bits_error_ident> {
+                if Self::from_bits_retain(bits).without(Self::all_flags()).is_none() {
+                    Ok(Self { bits })
+                } else {
+                    Err(#from_bits_error_ident { bits })
+                }
+            }
+
+            /// Returns a bitmask with the given bits,
+            /// removing all bits that do not correspond to a flag.
+            #[inline]
+            #vis const fn from_bits_truncate(bits: #typ) -> Self {
+                Self { bits }.truncate()
+            }
+
+            /// Returns a bitmask with the given bits,
+            /// including bits that do not correspond to a flag.
             ///
-            /// This will include bits that do not have any flags.
-            /// Use `::all_flags()` if you only want to use flags.
+            /// This is equivalent to `From<#typ>`.
             #[inline]
-            #vis const fn all_bits() -> Self {
-                Self { bits: !0 }
+            #retain_vis const fn from_bits_retain(bits: #typ) -> Self {
+                Self { bits }
             }
 
-            /// Returns a bitmask that contains all flags.
+            #all_bits_impl
+
+            /// Returns a bitmask that contains all flags,
+            /// inverted flags are not taken into account.
             #[inline]
             #vis const fn all_flags() -> Self {
-                Self { bits: #(Self::#all_flags.bits |)* 0 }
+                let mut bm = Self::none();
+                #(#variants_cfgs
+                {
+                    bm = bm.or(Self::#variants);
+                })*
+                bm
             }
 
             /// Returns `true` if the bitmask contains all values.
@@ -159,7 +1692,7 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             /// use `.is_all_flags()` if you only want to check for all flags
             #[inline]
             #vis const fn is_all_bits(&self) -> bool {
-                self.bits == !0
+                #is_all_bits
             }
 
             /// Returns `true` if the bitmask contains all flags.
@@ -168,7 +1701,7 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             /// consider using `.truncate()` first.
             #[inline]
             #vis const fn is_all_flags(&self) -> bool {
-                self.bits == Self::all_flags().bits
+                #is_all_flags
             }
 
             /// Returns a bitmask that contains all values.
@@ -212,28 +1745,29 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             /// Returns a bitmask that does not contain any values.
             #[inline]
             #vis const fn none() -> Self {
-                Self { bits: 0 }
+                Self { bits: #zero }
             }
 
             /// Returns `true` if the bitmask does not contain any values.
             #[inline]
             #vis const fn is_none(&self) -> bool {
-                self.bits == 0
+                #is_none
             }
 
             /// Returns a bitmask that only has bits corresponding to flags
             #[inline]
             #vis const fn truncate(&self) -> Self {
-                Self { bits: self.bits & Self::all_flags().bits }
+                self.and(Self::all_flags())
             }
 
             /// Returns `true` if `self` intersects with any value in `other`,
             /// or if `other` does not contain any values.
             ///
-            /// This is equivalent to `(self & other) != 0 || other == 0`.
+            /// This is equivalent to `(self & other) != 0 || other == 0`,
+            /// use `.intersects_any()` if an empty `other` should return `false`.
             #[inline]
             #vis const fn intersects(&self, other: Self) -> bool {
-                (self.bits & other.bits) != 0 || other.bits == 0
+                !self.and(other).is_none() || other.is_none()
             }
 
             /// Returns `true` if `self` contains all values of `other`.
@@ -241,31 +1775,145 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             /// This is equivalent to  `(self & other) == other`.
             #[inline]
             #vis const fn contains(&self, other: Self) -> bool {
-                (self.bits & other.bits) == other.bits
+                #contains
+            }
+
+            /// Returns `true` if `self` intersects with any value in `other`.
+            ///
+            /// This is equivalent to `(self & other) != 0`.
+            #[inline]
+            #vis const fn intersects_any(&self, other: Self) -> bool {
+                !self.and(other).is_none()
+            }
+
+            /// Returns `true` if `self` and `other` do not have any values in common.
+            ///
+            /// This is equivalent to `(self & other) == 0`.
+            #[inline]
+            #vis const fn is_disjoint(&self, other: Self) -> bool {
+                self.and(other).is_none()
+            }
+
+            /// Returns `true` if all values of `self` are contained in `other`.
+            ///
+            /// This is equivalent to `other.contains(self)`.
+            #[inline]
+            #vis const fn is_subset(&self, other: Self) -> bool {
+                other.contains(*self)
+            }
+
+            /// Returns `true` if all values of `other` are contained in `self`.
+            ///
+            /// This is equivalent to `self.contains(other)`.
+            #[inline]
+            #vis const fn is_superset(&self, other: Self) -> bool {
+                self.contains(other)
+            }
+
+            /// Returns the values that are in `self` or in `other`.
+            ///
+            /// This is equivalent to `self | other`.
+            #[inline]
+            #vis const fn union(self, other: Self) -> Self {
+                self.or(other)
+            }
+
+            /// Returns the values that are in both `self` and `other`.
+            ///
+            /// This is equivalent to `self & other`.
+            #[inline]
+            #vis const fn intersection(self, other: Self) -> Self {
+                self.and(other)
+            }
+
+            /// Returns the values that are in `self` but not in `other`.
+            ///
+            /// This is equivalent to `self & !other`.
+            #[inline]
+            #vis const fn difference(self, other: Self) -> Self {
+                self.without(other)
+            }
+
+            /// Returns the values that are in either `self` or `other`, but not in both.
+            ///
+            /// This is equivalent to `self ^ other`.
+            #[inline]
+            #vis const fn symmetric_difference(self, other: Self) -> Self {
+                self.xor(other)
             }
 
-            /// Returns the bitwise NOT of the bitmask.
+            /// Returns all flags that are not in `self`.
+            ///
+            /// Unlike `.not()` this only contains bits corresponding to flags,
+            /// it is equivalent to `!self & Self::all_flags()`.
             #[inline]
-            #vis const fn not(self) -> Self {
-                Self { bits: !self.bits }
+            #vis const fn complement(self) -> Self {
+                self.not().truncate()
             }
 
+            #not_impl
+
             /// Returns the bitwise AND of the bitmask.
             #[inline]
             #vis const fn and(self, other: Self) -> Self {
-                Self { bits: self.bits & other.bits }
+                Self { bits: #and }
             }
 
             /// Returns the bitwise OR of the bitmask.
             #[inline]
             #vis const fn or(self, other: Self) -> Self {
-                Self { bits: self.bits | other.bits }
+                Self { bits: #or }
             }
 
             /// Returns the bitwise XOR of the bitmask.
             #[inline]
             #vis const fn xor(self, other: Self) -> Self {
-                Self { bits: self.bits ^ other.bits }
+                Self { bits: #xor }
+            }
+
+            /// Returns a bitmask with all values of `other` added.
+            ///
+            /// This is the const builder version of `.insert()`.
+            #[inline]
+            #vis const fn with(self, other: Self) -> Self {
+                self.or(other)
+            }
+
+            /// Returns a bitmask with all values of `other` removed.
+            ///
+            /// This is the const builder version of `.remove()`.
+            #[inline]
+            #vis const fn without(self, other: Self) -> Self {
+                Self { bits: #without }
+            }
+
+            /// Adds all values of `other` to the bitmask.
+            #[inline]
+            #vis fn insert(&mut self, other: Self) {
+                *self = self.or(other);
+            }
+
+            /// Removes all values of `other` from the bitmask.
+            #[inline]
+            #vis fn remove(&mut self, other: Self) {
+                *self = self.without(other);
+            }
+
+            /// Toggles all values of `other` in the bitmask.
+            #[inline]
+            #vis fn toggle(&mut self, other: Self) {
+                *self = self.xor(other);
+            }
+
+            /// Adds all values of `other` to the bitmask if `value` is `true`,
+            /// removes them otherwise.
+            #[inline]
+            #vis fn set(&mut self, other: Self, value: bool) {
+                if value {
+                    self.insert(other);
+                } else {
+                    self.remove(other);
+                }
             }
         }
 
@@ -273,7 +1921,7 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             type Output = Self;
             #[inline]
             fn not(self) -> Self::Output {
-                Self { bits: core::ops::Not::not(self.bits) }
+                Self::not(self)
             }
         }
 
@@ -281,14 +1929,14 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             type Output = Self;
             #[inline]
             fn bitand(self, rhs: Self) -> Self::Output {
-                Self { bits: core::ops::BitAnd::bitand(self.bits, rhs.bits) }
+                Self::and(self, rhs)
             }
         }
 
         impl core::ops::BitAndAssign for #ident {
             #[inline]
             fn bitand_assign(&mut self, rhs: Self) {
-                core::ops::BitAndAssign::bitand_assign(&mut self.bits, rhs.bits)
+                *self = Self::and(*self, rhs);
             }
         }
 
@@ -296,14 +1944,14 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             type Output = Self;
             #[inline]
             fn bitor(self, rhs: Self) -> Self::Output {
-                Self { bits: core::ops::BitOr::bitor(self.bits, rhs.bits) }
+                Self::or(self, rhs)
             }
         }
 
         impl core::ops::BitOrAssign for #ident {
             #[inline]
             fn bitor_assign(&mut self, rhs: Self) {
-                core::ops::BitOrAssign::bitor_assign(&mut self.bits, rhs.bits)
+                *self = Self::or(*self, rhs);
             }
         }
 
@@ -311,115 +1959,195 @@ pub fn parse(attr: TokenStream, mut item: ItemEnum) -> Result<TokenStream> {
             type Output = Self;
             #[inline]
             fn bitxor(self, rhs: Self) -> Self::Output {
-                Self { bits: core::ops::BitXor::bitxor(self.bits, rhs.bits) }
+                Self::xor(self, rhs)
             }
         }
 
         impl core::ops::BitXorAssign for #ident {
             #[inline]
             fn bitxor_assign(&mut self, rhs: Self) {
-                core::ops::BitXorAssign::bitxor_assign(&mut self.bits, rhs.bits)
+                *self = Self::xor(*self, rhs);
             }
         }
 
-        impl From<#typ> for #ident {
+        impl core::ops::Sub for #ident {
+            type Output = Self;
             #[inline]
-            fn from(val: #typ) -> Self {
-                Self { bits: val }
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self::without(self, rhs)
             }
         }
 
-        impl From<#ident> for #typ {
+        impl core::ops::SubAssign for #ident {
             #[inline]
-            fn from(val: #ident) -> #typ {
-                val.bits
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = Self::without(*self, rhs);
             }
         }
 
-        impl PartialEq<#typ> for #ident {
+        impl core::ops::Sub<#typ> for #ident {
+            type Output = Self;
             #[inline]
-            fn eq(&self, other: &#typ) -> bool {
-                self.bits == *other
+            fn sub(self, rhs: #typ) -> Self::Output {
+                Self::without(self, Self { bits: rhs })
             }
         }
 
-        #debug_impl
+        impl core::ops::SubAssign<#typ> for #ident {
+            #[inline]
+            fn sub_assign(&mut self, rhs: #typ) {
+                *self = Self::without(*self, Self { bits: rhs });
+            }
+        }
 
-        impl core::fmt::Binary for #ident {
+        impl core::iter::FromIterator<#ident> for #ident {
             #[inline]
-            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                core::fmt::Binary::fmt(&self.bits, f)
+            fn from_iter<I: core::iter::IntoIterator<Item = #ident>>(iter: I) -> Self {
+                let mut bm = Self::none();
+                bm.extend(iter);
+                bm
             }
         }
 
-        impl core::fmt::LowerHex for #ident {
+        impl<'a> core::iter::FromIterator<&'a #ident> for #ident {
             #[inline]
-            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                core::fmt::LowerHex::fmt(&self.bits, f)
+            fn from_iter<I: core::iter::IntoIterator<Item = &'a #ident>>(iter: I) -> Self {
+                iter.into_iter().copied().collect()
             }
         }
 
-        impl core::fmt::UpperHex for #ident {
+        impl core::iter::Extend<#ident> for #ident {
             #[inline]
-            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                core::fmt::UpperHex::fmt(&self.bits, f)
+            fn extend<I: core::iter::IntoIterator<Item = #ident>>(&mut self, iter: I) {
+                for bm in iter {
+                    self.insert(bm);
+                }
             }
         }
 
-        impl core::fmt::Octal for #ident {
+        impl core::iter::Sum<#ident> for #ident {
             #[inline]
-            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                core::fmt::Octal::fmt(&self.bits, f)
+            fn sum<I: core::iter::Iterator<Item = #ident>>(iter: I) -> Self {
+                iter.collect()
+            }
+        }
+
+        #from_typ_impl
+
+        impl From<#ident> for #typ {
+            #[inline]
+            fn from(val: #ident) -> #typ {
+                val.bits
+            }
+        }
+
+        impl PartialEq<#typ> for #ident {
+            #[inline]
+            fn eq(&self, other: &#typ) -> bool {
+                self.bits == *other
             }
         }
+
+        #signed_impl
+
+        #ord_impl
+
+        #default_impl
+
+        #bytemuck_impl
+
+        #flag_info_impl
+
+        #from_bits_error_impl
+
+        #parse_impl
+
+        #iter_impl
+
+        #debug_flags_impl
+
+        #debug_impl
+
+        #defmt_impl
+
+        #ufmt_impl
+
+        #arbitrary_impl
+
+        #proptest_impl
+
+        #schemars_impl
+
+        #display_impl
+
+        #serde_impl
+
+        #atomic_impl
+
+        #flag_enum_impl
+
+        #fmt_impls
     }))
 }
 
-fn parse_typ(attr: TokenStream) -> Result<Ident> {
-    if attr.is_empty() {
-        Ok(Ident::new("usize", Span::call_site().into()))
-    } else {
-        let ident = syn::parse::<Ident>(attr)?;
-        match ident.to_string().as_str() {
-            #[rustfmt::skip]
-            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" |
-            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => Ok(ident),
-            _ => Err(Error::new_spanned(
-                ident,
-                "type can only be an (un)signed integer",
-            )),
-        }
+/// Returns a const expression that is `1` if all `#[cfg]` attributes are enabled, `0` otherwise.
+fn cfg_count(cfgs: &[&Attribute]) -> Result<TokenStream2> {
+    if cfgs.is_empty() {
+        return Ok(quote::quote!(1));
     }
-}
 
-struct Config {
-    inverted_flags: bool,
-    vec_debug: bool,
-    flags_iter: bool,
+    let predicates = cfgs
+        .iter()
+        .map(|attr| Ok(attr.meta.require_list()?.tokens.clone()))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(quote::quote!((cfg!(all(#(#predicates),*)) as usize)))
 }
 
-impl Config {
-    fn new() -> Self {
-        Self {
-            inverted_flags: false,
-            vec_debug: false,
-            flags_iter: false,
+/// Returns the doc comment of the given attributes as a `&'static str` expression,
+/// with the leading space of each `///` line removed.
+fn doc_string(attrs: &[Attribute]) -> TokenStream2 {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => Some(match &meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(lit), .. }) => {
+                    let value = lit.value();
+                    let line = value.strip_prefix(' ').unwrap_or(&value);
+                    quote::quote!(#line)
+                }
+                expr => quote::quote!(#expr),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut doc = quote::quote!();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            doc.extend(quote::quote!("\n",));
         }
+        doc.extend(quote::quote!(#line,));
     }
+    quote::quote!(concat!(#doc))
 }
 
-impl Parse for Config {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let args = Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
-        let mut config = Self::new();
-        for arg in args {
-            match arg.to_string().as_str() {
-                "inverted_flags" => config.inverted_flags = true,
-                "vec_debug" => config.vec_debug = true,
-                "flags_iter" => config.flags_iter = true,
-                _ => return Err(Error::new_spanned(arg, "unknown config option")),
-            }
-        }
-        Ok(config)
-    }
+/// Returns a const expression building a `[(name, flag); amount]` table,
+/// skipping the flags disabled by `#[cfg]` attributes.
+fn flags_table(
+    ident: &Ident,
+    names: &[TokenStream2],
+    flags: &[Ident],
+    cfgs: &[TokenStream2],
+    amount: &TokenStream2,
+) -> TokenStream2 {
+    quote::quote!({
+        let mut table = [("", #ident::none()); #amount];
+        let mut i = 0;
+        #(#cfgs
+        {
+            table[i] = (#names, #ident::#flags);
+            i += 1;
+        })*
+        let _ = i;
+        table
+    })
 }
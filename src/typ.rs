@@ -0,0 +1,505 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{Error, Expr, Ident, Result, Type};
+
+/// The type of the bits of a bitmask.
+///
+/// Every operation on the raw bits is generated through this type,
+/// so the rest of the generated code works the same for integers and arrays.
+pub enum Typ {
+    /// An (un)signed integer, e.g. `u8`.
+    Int(Ident),
+    /// A fixed-size array of unsigned integer words, e.g. `[u64; 4]`.
+    Array(Ident, Expr),
+}
+
+impl Typ {
+    pub fn parse(attr: TokenStream) -> Result<Self> {
+        if attr.is_empty() {
+            return Ok(Self::Int(Ident::new("usize", Span::call_site())));
+        }
+
+        match syn::parse::<Type>(attr)? {
+            Type::Path(path) if path.path.get_ident().is_some() => {
+                let ident = path.path.get_ident().unwrap().clone();
+                match ident.to_string().as_str() {
+                    #[rustfmt::skip]
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" |
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => Ok(Self::Int(ident)),
+                    _ => Err(Error::new_spanned(
+                        ident,
+                        "type can only be an (un)signed integer or an array of unsigned integers",
+                    )),
+                }
+            }
+            Type::Array(array) => match &*array.elem {
+                Type::Path(path) if path.path.get_ident().is_some() => {
+                    let ident = path.path.get_ident().unwrap().clone();
+                    match ident.to_string().as_str() {
+                        "u8" | "u16" | "u32" | "u64" | "usize" => Ok(Self::Array(ident, array.len)),
+                        _ => Err(Error::new_spanned(
+                            ident,
+                            "array type can only have unsigned integer words of up to 64 bits",
+                        )),
+                    }
+                }
+                elem => Err(Error::new_spanned(
+                    elem,
+                    "array type can only have unsigned integer words of up to 64 bits",
+                )),
+            },
+            typ => Err(Error::new_spanned(
+                typ,
+                "type can only be an (un)signed integer or an array of unsigned integers",
+            )),
+        }
+    }
+
+    /// Returns the amount of bits as a `usize` expression.
+    pub fn bits_amount(&self) -> TokenStream2 {
+        match self {
+            Self::Int(typ) => quote::quote!((<#typ>::BITS as usize)),
+            Self::Array(word, len) => quote::quote!((<#word>::BITS as usize * (#len))),
+        }
+    }
+
+    /// Returns the unsigned integer type of the same width,
+    /// or `None` if the type is not a signed integer.
+    pub fn unsigned(&self) -> Option<Ident> {
+        match self {
+            Self::Int(typ) => {
+                let unsigned = match typ.to_string().as_str() {
+                    "i8" => "u8",
+                    "i16" => "u16",
+                    "i32" => "u32",
+                    "i64" => "u64",
+                    "i128" => "u128",
+                    "isize" => "usize",
+                    _ => return None,
+                };
+                Some(Ident::new(unsigned, typ.span()))
+            }
+            Self::Array(..) => None,
+        }
+    }
+
+    /// Returns the matching `core::sync::atomic` type
+    /// and its `target_has_atomic` width.
+    pub fn atomic(&self) -> Result<(Ident, &'static str)> {
+        let (atomic, width) = match self {
+            Self::Int(typ) => match typ.to_string().as_str() {
+                "u8" => ("AtomicU8", "8"),
+                "u16" => ("AtomicU16", "16"),
+                "u32" => ("AtomicU32", "32"),
+                "u64" => ("AtomicU64", "64"),
+                "usize" => ("AtomicUsize", "ptr"),
+                "i8" => ("AtomicI8", "8"),
+                "i16" => ("AtomicI16", "16"),
+                "i32" => ("AtomicI32", "32"),
+                "i64" => ("AtomicI64", "64"),
+                "isize" => ("AtomicIsize", "ptr"),
+                _ => return Err(Error::new_spanned(typ, "there is no atomic type for this type")),
+            },
+            Self::Array(..) => {
+                return Err(Error::new_spanned(self, "there is no atomic type for array types"))
+            }
+        };
+        Ok((Ident::new(atomic, Span::call_site()), width))
+    }
+
+    /// Returns the bits without any set bit.
+    pub fn zero(&self) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(0),
+            Self::Array(_, len) => quote::quote!([0; #len]),
+        }
+    }
+
+    /// Returns the bits with every bit set.
+    pub fn ones(&self) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(!0),
+            Self::Array(_, len) => quote::quote!([!0; #len]),
+        }
+    }
+
+    /// Returns the bits with only the bit at `shift` set,
+    /// panicking with `msg` at `span` if `shift` is out of range.
+    pub fn bit(&self, shift: &TokenStream2, msg: &str, span: Span) -> TokenStream2 {
+        match self {
+            Self::Int(typ) => {
+                let shift = quote::quote!((#shift) as u32);
+                quote::quote_spanned!(span=>
+                    match <#typ>::checked_shl(1, #shift) {
+                        Some(bits) => bits,
+                        None => panic!(#msg),
+                    }
+                )
+            }
+            Self::Array(word, len) => {
+                let check = quote::quote_spanned!(span=>
+                    if shift / <#word>::BITS as usize >= #len {
+                        panic!(#msg);
+                    }
+                );
+                quote::quote!({
+                    let shift = #shift;
+                    #check
+                    let mut bits = [0; #len];
+                    bits[shift / <#word>::BITS as usize] = 1 << (shift % <#word>::BITS as usize);
+                    bits
+                })
+            }
+        }
+    }
+
+    /// Returns the bitwise NOT of `a`.
+    pub fn not(&self, a: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(!#a),
+            Self::Array(_, len) => quote::quote!({
+                let mut bits = #a;
+                let mut i = 0;
+                while i < #len {
+                    bits[i] = !bits[i];
+                    i += 1;
+                }
+                bits
+            }),
+        }
+    }
+
+    /// Returns the bitwise `op` of `a` and `b`, where `op` is `&`, `|` or `^`.
+    pub fn binop(&self, a: TokenStream2, op: TokenStream2, b: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!((#a #op #b)),
+            Self::Array(_, len) => quote::quote!({
+                let (mut bits, other) = (#a, #b);
+                let mut i = 0;
+                while i < #len {
+                    bits[i] = bits[i] #op other[i];
+                    i += 1;
+                }
+                bits
+            }),
+        }
+    }
+
+    /// Returns the `core::cmp::Ordering` of the values of `a` and `b`,
+    /// or `None` if the derived ordering of the bits already matches it.
+    ///
+    /// Signed integers are compared by their unsigned bit pattern
+    /// and arrays from the highest word down.
+    pub fn cmp(&self, a: TokenStream2, b: TokenStream2) -> Option<TokenStream2> {
+        match self {
+            Self::Int(_) => {
+                let unsigned = self.unsigned()?;
+                Some(quote::quote!((#a as #unsigned).cmp(&(#b as #unsigned))))
+            }
+            Self::Array(..) => Some(quote::quote!(#a.iter().rev().cmp(#b.iter().rev()))),
+        }
+    }
+
+    /// Returns `true` if `a` and `b` are equal.
+    pub fn eq(&self, a: TokenStream2, b: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!((#a == #b)),
+            Self::Array(_, len) => quote::quote!({
+                let (bits, other) = (#a, #b);
+                let mut eq = true;
+                let mut i = 0;
+                while i < #len {
+                    eq &= bits[i] == other[i];
+                    i += 1;
+                }
+                eq
+            }),
+        }
+    }
+
+    /// Returns the amount of set bits of `a` as a `u32`.
+    pub fn count_ones(&self, a: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(#a.count_ones()),
+            Self::Array(_, len) => quote::quote!({
+                let bits = #a;
+                let mut count = 0;
+                let mut i = 0;
+                while i < #len {
+                    count += bits[i].count_ones();
+                    i += 1;
+                }
+                count
+            }),
+        }
+    }
+
+    /// Returns the index of the lowest set bit of `a` as a `usize`,
+    /// or the amount of bits if no bit is set.
+    pub fn trailing_zeros(&self, a: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!((#a.trailing_zeros() as usize)),
+            Self::Array(_, len) => quote::quote!({
+                let bits = #a;
+                let mut idx = 0;
+                let mut i = 0;
+                while i < #len {
+                    idx += bits[i].trailing_zeros() as usize;
+                    if bits[i] != 0 {
+                        break;
+                    }
+                    i += 1;
+                }
+                idx
+            }),
+        }
+    }
+
+    /// Returns the 4 bits of `a` at the hex digit `idx` as a `usize`.
+    pub fn nibble(&self, a: TokenStream2, idx: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(((#a >> (#idx * 4)) & 0xf) as usize),
+            Self::Array(word, _) => quote::quote!(
+                ((#a[#idx * 4 / <#word>::BITS as usize] >> (#idx * 4 % <#word>::BITS as usize)) & 0xf) as usize
+            ),
+        }
+    }
+
+    /// Clears the lowest set bit of the place `a`,
+    /// returning its index as `Some(usize)` or `None` if no bit is set.
+    pub fn pop_lowest(&self, a: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(typ) => quote::quote!({
+                if #a == 0 {
+                    None
+                } else {
+                    let idx = #a.trailing_zeros() as usize;
+                    let bit: #typ = 1 << idx;
+                    #a &= !bit;
+                    Some(idx)
+                }
+            }),
+            Self::Array(word, len) => quote::quote!({
+                let mut idx = None;
+                let mut i = 0;
+                while i < #len {
+                    if #a[i] != 0 {
+                        let bit = #a[i].trailing_zeros() as usize;
+                        #a[i] &= !(1 << bit);
+                        idx = Some(i * <#word>::BITS as usize + bit);
+                        break;
+                    }
+                    i += 1;
+                }
+                idx
+            }),
+        }
+    }
+
+    /// Parses the `digits` of the given `radix`, returning `Option` of the bits.
+    pub fn parse_radix(&self, digits: TokenStream2, radix: TokenStream2) -> TokenStream2 {
+        match self {
+            // Signed types are parsed as their bit pattern,
+            // so e.g. `0x80` is the sign bit of an `i8` instead of out of range.
+            Self::Int(typ) => {
+                let from_str_radix = match self.unsigned() {
+                    Some(unsigned) => quote::quote!(
+                        <#unsigned>::from_str_radix(#digits, #radix).ok().map(|bits| bits as #typ)
+                    ),
+                    None => quote::quote!(<#typ>::from_str_radix(#digits, #radix).ok()),
+                };
+                // `from_str_radix` accepts a leading sign, which the array parsing does not.
+                quote::quote!({
+                    if !#digits.is_empty() && #digits.chars().all(|c| c.is_digit(#radix)) {
+                        #from_str_radix
+                    } else {
+                        None
+                    }
+                })
+            }
+            Self::Array(word, len) => quote::quote!({
+                let mut bits: [#word; #len] = [0; #len];
+                let mut valid = !#digits.is_empty();
+                for c in #digits.chars() {
+                    let Some(digit) = c.to_digit(#radix) else {
+                        valid = false;
+                        break;
+                    };
+
+                    let mut carry = digit as u128;
+                    for word in bits.iter_mut() {
+                        let value = *word as u128 * #radix as u128 + carry;
+                        *word = value as #word;
+                        carry = value >> <#word>::BITS;
+                    }
+                    if carry != 0 {
+                        valid = false;
+                        break;
+                    }
+                }
+                valid.then_some(bits)
+            }),
+        }
+    }
+
+    /// Serializes the bits `a` with the `serializer`.
+    ///
+    /// Arrays are serialized as a tuple of their words like serde does,
+    /// which only implements `Serialize` for arrays of up to 32 elements.
+    pub fn serialize(&self, a: TokenStream2, serializer: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote!(::serde::Serialize::serialize(&#a, #serializer)),
+            Self::Array(_, len) => quote::quote!({
+                let mut tuple = ::serde::Serializer::serialize_tuple(#serializer, #len)?;
+                for word in #a.iter() {
+                    ::serde::ser::SerializeTuple::serialize_element(&mut tuple, word)?;
+                }
+                ::serde::ser::SerializeTuple::end(tuple)
+            }),
+        }
+    }
+
+    /// Deserializes bits with the `deserializer`, returning `Result` of the bits.
+    pub fn deserialize(&self, deserializer: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(typ) => quote::quote!(<#typ as ::serde::Deserialize>::deserialize(#deserializer)),
+            Self::Array(word, len) => quote::quote!({
+                struct WordsVisitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for WordsVisitor {
+                    type Value = [#word; #len];
+
+                    fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        write!(f, "an array of {} words", #len)
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: ::serde::de::SeqAccess<'de>,
+                    {
+                        let mut bits: [#word; #len] = [0; #len];
+                        for (i, word) in bits.iter_mut().enumerate() {
+                            *word = seq
+                                .next_element()?
+                                .ok_or_else(|| ::serde::de::Error::invalid_length(i, &self))?;
+                        }
+                        Ok(bits)
+                    }
+                }
+
+                ::serde::Deserializer::deserialize_tuple(#deserializer, #len, WordsVisitor)
+            }),
+        }
+    }
+
+    /// Returns the `Binary`, `LowerHex`, `UpperHex` and `Octal` impls of the bitmask.
+    pub fn fmt_impls(&self, ident: &Ident) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote::quote! {
+                impl core::fmt::Binary for #ident {
+                    #[inline]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        core::fmt::Binary::fmt(&self.bits, f)
+                    }
+                }
+
+                impl core::fmt::LowerHex for #ident {
+                    #[inline]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        core::fmt::LowerHex::fmt(&self.bits, f)
+                    }
+                }
+
+                impl core::fmt::UpperHex for #ident {
+                    #[inline]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        core::fmt::UpperHex::fmt(&self.bits, f)
+                    }
+                }
+
+                impl core::fmt::Octal for #ident {
+                    #[inline]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        core::fmt::Octal::fmt(&self.bits, f)
+                    }
+                }
+            },
+            Self::Array(word, len) => quote::quote! {
+                const _: () = {
+                    // Formats the bits as a single number, from the last word to the first,
+                    // with `shift` bits per digit.
+                    fn fmt_radix(
+                        bits: &[#word; #len],
+                        f: &mut core::fmt::Formatter<'_>,
+                        shift: usize,
+                        digits: &[u8; 16],
+                        prefix: &str,
+                    ) -> core::fmt::Result {
+                        const BITS: usize = <#word>::BITS as usize * (#len);
+
+                        let mut buf = [0u8; BITS];
+                        let mut len = 0;
+                        let mut d = BITS.div_ceil(shift);
+                        while d > 0 {
+                            d -= 1;
+
+                            let mut digit = 0;
+                            for b in 0..shift {
+                                let idx = d * shift + b;
+                                let word = idx / <#word>::BITS as usize;
+                                if idx < BITS && (bits[word] >> (idx % <#word>::BITS as usize)) & 1 == 1 {
+                                    digit |= 1 << b;
+                                }
+                            }
+
+                            if digit != 0 || len != 0 || d == 0 {
+                                buf[len] = digits[digit];
+                                len += 1;
+                            }
+                        }
+
+                        f.pad_integral(true, prefix, core::str::from_utf8(&buf[..len]).unwrap())
+                    }
+
+                    impl core::fmt::Binary for #ident {
+                        #[inline]
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            fmt_radix(&self.bits, f, 1, b"0123456789abcdef", "0b")
+                        }
+                    }
+
+                    impl core::fmt::LowerHex for #ident {
+                        #[inline]
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            fmt_radix(&self.bits, f, 4, b"0123456789abcdef", "0x")
+                        }
+                    }
+
+                    impl core::fmt::UpperHex for #ident {
+                        #[inline]
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            fmt_radix(&self.bits, f, 4, b"0123456789ABCDEF", "0x")
+                        }
+                    }
+
+                    impl core::fmt::Octal for #ident {
+                        #[inline]
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            fmt_radix(&self.bits, f, 3, b"0123456789abcdef", "0o")
+                        }
+                    }
+                };
+            },
+        }
+    }
+}
+
+impl ToTokens for Typ {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            Self::Int(typ) => typ.to_tokens(tokens),
+            Self::Array(word, len) => tokens.extend(quote::quote!([#word; #len])),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use bitmask_enum::bitmask;
+
+    #[bitmask(u16)]
+    #[bitmask_config(arbitrary)]
+    enum BitmaskArbitrary {
+        Flag1,
+        Flag2,
+        #[bitmask_flag(composite)]
+        Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        Flag3,
+    }
+
+    #[bitmask([u8; 2])]
+    #[bitmask_config(arbitrary(unknown_bits = 0))]
+    enum BitmaskArbitraryKnown {
+        Flag1,
+        Flag16 = [0, 0b1000_0000],
+    }
+
+    #[test]
+    fn test_arbitrary() {
+        let data = (0..=255u8).cycle().take(4096).collect::<Vec<_>>();
+        let mut u = Unstructured::new(&data);
+
+        let mut seen = BitmaskArbitrary::none();
+        let mut unknown = false;
+        for _ in 0..128 {
+            let bm = BitmaskArbitrary::arbitrary(&mut u).unwrap();
+            seen.insert(bm.truncate());
+            unknown |= !bm.without(BitmaskArbitrary::all_flags()).is_none();
+        }
+        assert!(seen.is_all_flags());
+        assert!(unknown);
+
+        for _ in 0..128 {
+            let bm = BitmaskArbitraryKnown::arbitrary(&mut u).unwrap();
+            assert_eq!(bm, bm.truncate());
+        }
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let mut u = Unstructured::new(&[]);
+        assert_eq!(
+            BitmaskArbitrary::arbitrary(&mut u).unwrap(),
+            BitmaskArbitrary::none()
+        );
+    }
+}
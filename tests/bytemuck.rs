@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+
+    #[bitmask(u8)]
+    #[bitmask_config(bytemuck)]
+    enum BitmaskBytemuck {
+        Flag1,
+        Flag2,
+    }
+
+    #[bitmask([u8; 3])]
+    #[bitmask_config(bytemuck)]
+    enum BitmaskBytemuckWide {
+        Flag1,
+        Flag24 = [0, 0, 0b1000_0000],
+    }
+
+    #[test]
+    fn test_cast() {
+        let bytes: [u8; 3] = [0x01, 0x02, 0x83];
+        let masks: &[BitmaskBytemuck] = bytemuck::cast_slice(&bytes);
+        assert_eq!(
+            masks,
+            [
+                BitmaskBytemuck::Flag1,
+                BitmaskBytemuck::Flag2,
+                BitmaskBytemuck::from(0x83),
+            ]
+        );
+        assert_eq!(bytemuck::cast_slice::<_, u8>(masks), bytes);
+
+        let wide: BitmaskBytemuckWide = bytemuck::cast([0x01u8, 0x00, 0x80]);
+        assert_eq!(
+            wide,
+            BitmaskBytemuckWide::Flag1 | BitmaskBytemuckWide::Flag24
+        );
+        assert_eq!(bytemuck::bytes_of(&wide), [0x01, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_zeroed() {
+        assert_eq!(
+            <BitmaskBytemuck as bytemuck::Zeroable>::zeroed(),
+            BitmaskBytemuck::none()
+        );
+    }
+}
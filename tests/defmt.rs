@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+
+    #[bitmask(u8)]
+    #[bitmask_config(defmt)]
+    enum BitmaskDefmt {
+        Flag1,
+        Flag2,
+        #[bitmask_flag(composite)]
+        Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        #[cfg(not(test))]
+        Disabled,
+        Flag3,
+    }
+
+    #[bitmask([u32; 2])]
+    #[bitmask_config(defmt)]
+    enum BitmaskDefmtWide {
+        Flag1,
+    }
+
+    #[bitmask(i16)]
+    #[bitmask_config(defmt)]
+    enum BitmaskDefmtSigned {
+        Flag1,
+    }
+
+    fn format<T: defmt::Format>(bm: T) -> Vec<u8> {
+        defmt::export::fetch_bytes();
+        defmt::Format::format(&bm, defmt::export::make_formatter());
+        defmt::export::fetch_bytes()
+    }
+
+    #[test]
+    fn test_format() {
+        // the mocked interner returns a new index for every interned string,
+        // which is written as two bytes
+        let index = defmt::export::fetch_string_index() as u8;
+        assert_eq!(
+            format(BitmaskDefmt::none()),
+            [index + 1, 0, index, 0, index + 2, 0]
+        );
+
+        // name, `{=istr}` and name of the flag, `]`
+        assert_eq!(format(BitmaskDefmt::Flag12).len(), 2 * 5);
+        // additionally `, `, `{=istr}` and name of the second flag
+        assert_eq!(
+            format(BitmaskDefmt::Flag1 | BitmaskDefmt::Flag3).len(),
+            2 * 8
+        );
+
+        // additionally `, `, `{:#x}`, the format of `u8` and the remaining bits
+        let bytes = format(BitmaskDefmt::from(0b1001));
+        assert_eq!(bytes.len(), 2 * 8 + 1);
+        assert_eq!(bytes[bytes.len() - 3], 0b1000);
+
+        assert!(format(BitmaskDefmtWide::all_bits()).len() > 2 * 7);
+        assert!(format(BitmaskDefmtSigned::all_bits()).len() > 2 * 7);
+    }
+}
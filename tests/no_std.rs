@@ -5,19 +5,32 @@ mod tests {
     use bitmask_enum::bitmask;
 
     #[bitmask]
-    #[bitmask_config(inverted_flags, vec_debug, flags_iter)]
+    #[bitmask_config(inverted_flags, vec_debug, flags_iter, display, atomic)]
     enum NoStdBitmask {
         Flag1,
         Flag2,
     }
 
     #[bitmask(u8)]
-    #[bitmask_config(inverted_flags, vec_debug, flags_iter)]
+    #[bitmask_config(inverted_flags, vec_debug, flags_iter, display, atomic)]
     enum NoStdBitmaskU8 {
         Flag1,
         Flag2,
     }
 
+    #[bitmask(u8)]
+    #[bitmask_config(schemars)]
+    enum NoStdBitmaskSchema {
+        /// The first flag.
+        Flag1,
+    }
+
+    #[bitmask([u8; 2])]
+    #[bitmask_config(schemars = "bits")]
+    enum NoStdBitmaskSchemaBits {
+        Flag1,
+    }
+
     #[test]
     fn test() {
         let mut bm = NoStdBitmask::none();
@@ -27,7 +40,7 @@ mod tests {
         assert_eq!(bm, NoStdBitmask::Flag1);
 
         bm |= NoStdBitmask::InvertedFlag1;
-        assert_eq!(bm.is_all_bits(), true);
+        assert!(bm.is_all_bits());
 
         let mut bm = NoStdBitmaskU8::none();
         assert_eq!(bm, 0);
@@ -36,6 +49,43 @@ mod tests {
         assert_eq!(bm, NoStdBitmaskU8::Flag1);
 
         bm |= NoStdBitmaskU8::InvertedFlag1;
-        assert_eq!(bm.is_all_bits(), true);
+        assert!(bm.is_all_bits());
+    }
+
+    #[test]
+    fn test_collect() {
+        let flags = [NoStdBitmask::Flag1, NoStdBitmask::Flag2];
+        assert_eq!(
+            flags.iter().collect::<NoStdBitmask>(),
+            flags.into_iter().sum::<NoStdBitmask>()
+        );
+
+        let mut bm = NoStdBitmaskU8::none();
+        bm.extend([NoStdBitmaskU8::Flag2]);
+        assert_eq!(bm, NoStdBitmaskU8::Flag2);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "Flag1 | Flag2".parse::<NoStdBitmask>(),
+            Ok(NoStdBitmask::Flag1 | NoStdBitmask::Flag2)
+        );
+        assert_eq!(
+            "InvertedFlag2 | 0x2".parse::<NoStdBitmaskU8>(),
+            Ok(NoStdBitmaskU8::all_bits())
+        );
+        assert!("Flag3".parse::<NoStdBitmaskU8>().is_err());
+    }
+
+    #[test]
+    fn test_schemars() {
+        use schemars::JsonSchema;
+
+        assert_eq!(NoStdBitmaskSchema::schema_name(), "NoStdBitmaskSchema");
+        assert_eq!(
+            NoStdBitmaskSchemaBits::schema_name(),
+            "NoStdBitmaskSchemaBits"
+        );
     }
-}
\ No newline at end of file
+}
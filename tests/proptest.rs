@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+    use proptest::prelude::*;
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    #[bitmask(u8)]
+    #[bitmask_config(proptest(unknown_bits = 50))]
+    enum BitmaskProptest {
+        Flag1,
+        Flag2,
+        Flag3,
+    }
+
+    #[bitmask([u8; 2])]
+    #[bitmask_config(proptest(unknown_bits = 0))]
+    enum BitmaskProptestKnown {
+        Flag1,
+        Flag16 = [0, 0b1000_0000],
+    }
+
+    #[bitmask([u64; 2])]
+    #[bitmask_config(strict, proptest)]
+    enum BitmaskProptestStrict {
+        Flag1,
+        Flag128 = [0, 1 << 63],
+    }
+
+    fn sample<T: Arbitrary>(runner: &mut TestRunner) -> T {
+        any::<T>().new_tree(runner).unwrap().current()
+    }
+
+    #[test]
+    fn test_strategy() {
+        let mut runner = TestRunner::deterministic();
+
+        let mut seen = BitmaskProptest::none();
+        let mut unknown = false;
+        for _ in 0..128 {
+            let bm = sample::<BitmaskProptest>(&mut runner);
+            seen.insert(bm.truncate());
+            unknown |= !bm.without(BitmaskProptest::all_flags()).is_none();
+        }
+        assert!(seen.is_all_flags());
+        assert!(unknown);
+
+        let mut seen = BitmaskProptestKnown::none();
+        for _ in 0..128 {
+            let bm = sample::<BitmaskProptestKnown>(&mut runner);
+            assert_eq!(bm, bm.truncate());
+            seen.insert(bm);
+        }
+        assert!(seen.is_all_flags());
+    }
+
+    proptest! {
+        #[test]
+        fn test_strict(bm: BitmaskProptestStrict) {
+            prop_assert_eq!(BitmaskProptestStrict::from_bits(bm.bits()), Some(bm));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+    use schemars::{json_schema, schema_for};
+
+    #[bitmask(u8)]
+    #[bitmask_config(schemars, inverted_flags)]
+    enum BitmaskNames {
+        Flag1,
+        Flag2,
+        #[cfg(not(test))]
+        Disabled,
+        #[bitmask_flag(composite)]
+        Flag12 = Self::Flag1.or(Self::Flag2).bits,
+    }
+
+    /// A documented bitmask.
+    #[bitmask(u16)]
+    #[bitmask_config(schemars = "names", strict)]
+    enum BitmaskDocs {
+        /// The first flag.
+        Flag1,
+        Flag2,
+        /// The third flag
+        /// over two lines.
+        Flag3,
+    }
+
+    #[bitmask(u8)]
+    #[bitmask_config(schemars, strict)]
+    enum BitmaskStrict {
+        Flag1,
+        Flag2,
+    }
+
+    #[bitmask(u32)]
+    #[bitmask_config(schemars = "bits")]
+    enum BitmaskBits {
+        Flag1,
+        Flag2,
+        Flag3,
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            schema_for!(BitmaskNames),
+            json_schema!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "BitmaskNames",
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "oneOf": [
+                        { "enum": ["Flag1", "Flag2", "Flag12"] },
+                        {
+                            "pattern": "^0x[0-9a-f]+$",
+                            "description": "Undeclared bits as a hex number.",
+                        },
+                    ],
+                },
+                "uniqueItems": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict() {
+        assert_eq!(
+            schema_for!(BitmaskStrict),
+            json_schema!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "BitmaskStrict",
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "enum": ["Flag1", "Flag2"],
+                },
+                "uniqueItems": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_docs() {
+        assert_eq!(
+            schema_for!(BitmaskDocs),
+            json_schema!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "BitmaskDocs",
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "oneOf": [
+                        { "const": "Flag1", "description": "The first flag." },
+                        { "const": "Flag2" },
+                        { "const": "Flag3", "description": "The third flag\nover two lines." },
+                    ],
+                },
+                "uniqueItems": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(
+            schema_for!(BitmaskBits),
+            json_schema!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "BitmaskBits",
+                "type": "integer",
+                "format": "uint32",
+                "minimum": 0,
+                "description": "A bitmask of the flags Flag1 = 0x1, Flag2 = 0x2, Flag3 = 0x4",
+            })
+        );
+    }
+}
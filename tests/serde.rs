@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+
+    #[bitmask(u8)]
+    #[bitmask_config(serde)]
+    enum BitmaskSerde {
+        Flag1,
+        Flag2,
+        #[bitmask_flag(composite)]
+        Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        Flag3,
+    }
+
+    #[bitmask(u8)]
+    #[bitmask_config(serde(unknown_bits = "truncate"))]
+    enum BitmaskSerdeTruncate {
+        Flag1,
+        Flag2,
+    }
+
+    #[bitmask(u8)]
+    #[bitmask_config(serde(unknown_bits = "retain"))]
+    enum BitmaskSerdeRetain {
+        Flag1,
+        Flag2,
+    }
+
+    #[bitmask([u8; 40])]
+    #[bitmask_config(serde)]
+    enum BitmaskSerdeWide {
+        Flag1,
+        Flag320 = {
+            let mut bits = [0; 40];
+            bits[39] = 0b1000_0000;
+            bits
+        },
+    }
+
+    #[test]
+    fn test_human_readable() {
+        let cases = [
+            (BitmaskSerde::none(), r#"[]"#),
+            (BitmaskSerde::Flag3, r#"["Flag3"]"#),
+            (
+                BitmaskSerde::Flag1 | BitmaskSerde::Flag3,
+                r#"["Flag1","Flag3"]"#,
+            ),
+            (BitmaskSerde::Flag12, r#"["Flag1","Flag2","Flag12"]"#),
+        ];
+        for (bm, json) in cases {
+            assert_eq!(serde_json::to_string(&bm).unwrap(), json);
+            assert_eq!(serde_json::from_str::<BitmaskSerde>(json).unwrap(), bm);
+        }
+
+        assert!(serde_json::from_str::<BitmaskSerde>(r#"["Flag4"]"#).is_err());
+        assert!(serde_json::from_str::<BitmaskSerde>(r#"["Flag1","0x80"]"#).is_err());
+    }
+
+    #[test]
+    fn test_binary() {
+        let bm = BitmaskSerde::Flag1 | BitmaskSerde::Flag3;
+        let bytes = bincode::serialize(&bm).unwrap();
+        assert_eq!(bytes, [0b101]);
+        assert_eq!(bincode::deserialize::<BitmaskSerde>(&bytes).unwrap(), bm);
+
+        assert!(bincode::deserialize::<BitmaskSerde>(&[0b1001]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_bits() {
+        assert_eq!(
+            serde_json::from_str::<BitmaskSerdeTruncate>(r#"["Flag1","0x80"]"#).unwrap(),
+            BitmaskSerdeTruncate::Flag1
+        );
+        assert_eq!(
+            bincode::deserialize::<BitmaskSerdeTruncate>(&[0b1001]).unwrap(),
+            BitmaskSerdeTruncate::Flag1
+        );
+
+        let bm = BitmaskSerdeRetain::from(0b1000_0010);
+        let json = serde_json::to_string(&bm).unwrap();
+        assert_eq!(json, r#"["Flag2","0x80"]"#);
+        assert_eq!(
+            serde_json::from_str::<BitmaskSerdeRetain>(&json).unwrap(),
+            bm
+        );
+        assert_eq!(
+            bincode::deserialize::<BitmaskSerdeRetain>(&[0b1001]).unwrap(),
+            0b1001
+        );
+    }
+
+    #[test]
+    fn test_wide() {
+        let bm = BitmaskSerdeWide::Flag1 | BitmaskSerdeWide::Flag320;
+        let bytes = bincode::serialize(&bm).unwrap();
+        assert_eq!(bytes, bm.bits());
+        assert_eq!(
+            bincode::deserialize::<BitmaskSerdeWide>(&bytes).unwrap(),
+            bm
+        );
+
+        assert!(bincode::deserialize::<BitmaskSerdeWide>(&bytes[..39]).is_err());
+        assert!(bincode::deserialize::<BitmaskSerdeWide>(&[0b10; 40]).is_err());
+    }
+}
@@ -29,34 +29,34 @@ mod tests {
         assert_eq!(bm, 0b10000000);
 
         bm |= !Bitmask::Flag8;
-        assert_eq!(bm.is_all_bits(), true);
+        assert!(bm.is_all_bits());
     }
 
     #[test]
     fn test_bits() {
         let all = Bitmask::all_bits();
-        assert_eq!(all.bits(), std::usize::MAX);
+        assert_eq!(all.bits(), usize::MAX);
     }
 
     #[test]
     fn test_all_bits() {
         let all = Bitmask::all_bits();
-        assert_eq!(all.is_all_bits(), true);
-        assert_eq!(all, std::usize::MAX);
+        assert!(all.is_all_bits());
+        assert_eq!(all, usize::MAX);
     }
 
     #[test]
     fn test_none() {
         let none = Bitmask::none();
-        assert_eq!(none.is_none(), true);
-        assert_eq!(none, std::usize::MIN);
+        assert!(none.is_none());
+        assert_eq!(none, usize::MIN);
     }
 
     #[test]
 
     fn test_all_flags() {
         let all_flags = Bitmask::all_flags();
-        assert_eq!(all_flags.is_all_flags(), true);
+        assert!(all_flags.is_all_flags());
         assert_eq!(
             all_flags,
             Bitmask::Flag1
@@ -73,26 +73,133 @@ mod tests {
     #[test]
     fn test_truncate() {
         let all = Bitmask::all_bits();
-        assert_eq!(all.is_all_bits(), true);
-        assert_eq!(all.is_all_flags(), false);
-        assert_eq!(all.truncate().is_all_flags(), true);
+        assert!(all.is_all_bits());
+        assert!(!all.is_all_flags());
+        assert!(all.truncate().is_all_flags());
     }
 
     #[test]
     fn test_intersects() {
         let bm = Bitmask::Flag4;
-        assert_eq!(bm.intersects(Bitmask::Flag4), true);
-        assert_eq!(bm.intersects(Bitmask::Flag4 | Bitmask::Flag1), true);
-        assert_eq!(bm.intersects(Bitmask::Flag1), false);
+        assert!(bm.intersects(Bitmask::Flag4));
+        assert!(bm.intersects(Bitmask::Flag4 | Bitmask::Flag1));
+        assert!(!bm.intersects(Bitmask::Flag1));
+    }
+
+    #[test]
+    fn test_intersects_any() {
+        let bm = Bitmask::Flag4;
+        assert!(bm.intersects_any(Bitmask::Flag4 | Bitmask::Flag1));
+        assert!(!bm.intersects_any(Bitmask::Flag1));
+        assert!(!bm.intersects_any(Bitmask::none()));
+        assert!(bm.intersects(Bitmask::none()));
+    }
+
+    #[test]
+    fn test_set_predicates() {
+        let bm = Bitmask::Flag1 | Bitmask::Flag2;
+        assert!(bm.is_subset(bm | Bitmask::Flag3));
+        assert!(bm.is_subset(bm));
+        assert!(!bm.is_subset(Bitmask::Flag1));
+        assert!(Bitmask::none().is_subset(bm));
+
+        assert!(bm.is_superset(Bitmask::Flag2));
+        assert!(!bm.is_superset(Bitmask::Flag3));
+
+        assert!(bm.is_disjoint(Bitmask::Flag3));
+        assert!(bm.is_disjoint(Bitmask::none()));
+        assert!(!bm.is_disjoint(Bitmask::Flag2 | Bitmask::Flag3));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        const A: Bitmask = Bitmask::Flag1.union(Bitmask::Flag2);
+        const B: Bitmask = Bitmask::Flag2.union(Bitmask::Flag3);
+
+        assert_eq!(A.union(B), 0b111);
+        assert_eq!(A.intersection(B), 0b010);
+        assert_eq!(A.difference(B), 0b001);
+        assert_eq!(A.symmetric_difference(B), 0b101);
+        assert_eq!(A.complement(), 0b11111100);
+        assert_eq!(Bitmask::all_bits().complement(), Bitmask::none());
     }
 
     #[test]
     fn test_contains() {
         let bm = Bitmask::Flag4 | Bitmask::Flag6;
-        assert_eq!(bm.contains(Bitmask::Flag4), true);
-        assert_eq!(bm.contains(Bitmask::Flag4 | Bitmask::Flag6), true);
-        assert_eq!(bm.contains(Bitmask::Flag1), false);
-        assert_eq!(bm.contains(Bitmask::Flag4 | Bitmask::Flag1), false);
+        assert!(bm.contains(Bitmask::Flag4));
+        assert!(bm.contains(Bitmask::Flag4 | Bitmask::Flag6));
+        assert!(!bm.contains(Bitmask::Flag1));
+        assert!(!bm.contains(Bitmask::Flag4 | Bitmask::Flag1));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut bm = Bitmask::none();
+
+        bm.insert(Bitmask::Flag1 | Bitmask::Flag3);
+        assert_eq!(bm, 0b101);
+
+        bm.remove(Bitmask::Flag1 | Bitmask::Flag2);
+        assert_eq!(bm, 0b100);
+
+        bm.toggle(Bitmask::Flag2 | Bitmask::Flag3);
+        assert_eq!(bm, 0b010);
+
+        bm.set(Bitmask::Flag4, true);
+        assert_eq!(bm, 0b1010);
+
+        bm.set(Bitmask::Flag2, false);
+        assert_eq!(bm, 0b1000);
+    }
+
+    #[test]
+    fn test_with_without() {
+        const BM: Bitmask = Bitmask::none()
+            .with(Bitmask::Flag1.or(Bitmask::Flag2))
+            .without(Bitmask::Flag1);
+        assert_eq!(BM, Bitmask::Flag2);
+        assert_eq!(Bitmask::Flag1.with(Bitmask::Flag1), Bitmask::Flag1);
+        assert_eq!(Bitmask::Flag1.without(Bitmask::Flag2), Bitmask::Flag1);
+    }
+
+    #[test]
+    fn test_sub() {
+        let bm = Bitmask::Flag1 | Bitmask::Flag2 | Bitmask::Flag3;
+        assert_eq!(bm - Bitmask::Flag2, Bitmask::Flag1 | Bitmask::Flag3);
+        assert_eq!(bm - Bitmask::Flag4, bm);
+        assert_eq!(bm - (Bitmask::Flag1 | Bitmask::Flag4), bm & !Bitmask::Flag1);
+        assert_eq!(bm - 0b110, Bitmask::Flag1);
+
+        let mut bm = bm;
+        bm -= Bitmask::Flag1;
+        assert_eq!(bm, Bitmask::Flag2 | Bitmask::Flag3);
+        bm -= 0b100;
+        assert_eq!(bm, Bitmask::Flag2);
+    }
+
+    #[test]
+    fn test_collect() {
+        let flags = vec![Bitmask::Flag1, Bitmask::Flag3, Bitmask::Flag1];
+        assert_eq!(
+            flags.iter().collect::<Bitmask>(),
+            Bitmask::Flag1 | Bitmask::Flag3
+        );
+        assert_eq!(
+            flags.into_iter().collect::<Bitmask>(),
+            Bitmask::Flag1 | Bitmask::Flag3
+        );
+        assert_eq!(
+            core::iter::empty::<Bitmask>().collect::<Bitmask>(),
+            Bitmask::none()
+        );
+
+        let mut bm = Bitmask::Flag2;
+        bm.extend([Bitmask::Flag4, Bitmask::Flag5]);
+        assert_eq!(bm, Bitmask::Flag2 | Bitmask::Flag4 | Bitmask::Flag5);
+
+        let sum: Bitmask = [Bitmask::Flag6, Bitmask::Flag7].into_iter().sum();
+        assert_eq!(sum, Bitmask::Flag6 | Bitmask::Flag7);
     }
 
     #[test]
@@ -106,6 +213,86 @@ mod tests {
         assert_eq!(value, mask);
     }
 
+    #[test]
+    fn test_from_bits() {
+        #[bitmask(u8)]
+        enum BitmaskFromBits {
+            Flag1,
+            Flag2,
+            Flag3,
+        }
+
+        assert_eq!(
+            BitmaskFromBits::from_bits(0b101),
+            Some(BitmaskFromBits::Flag1 | BitmaskFromBits::Flag3)
+        );
+        assert_eq!(BitmaskFromBits::from_bits(0b1001), None);
+
+        let err = BitmaskFromBits::try_from_bits(0b11001).unwrap_err();
+        assert_eq!(err.bits(), 0b11001);
+        assert_eq!(err.unknown_bits(), 0b11000);
+        assert_eq!(
+            err.to_string(),
+            "unknown bits 0x18 for BitmaskFromBits in 0x19"
+        );
+
+        assert_eq!(
+            BitmaskFromBits::from_bits_truncate(0b11001),
+            BitmaskFromBits::Flag1
+        );
+        assert_eq!(BitmaskFromBits::from_bits_retain(0b11001), 0b11001);
+
+        const BM: Option<BitmaskFromBits> = BitmaskFromBits::from_bits(0b10);
+        assert_eq!(BM, Some(BitmaskFromBits::Flag2));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Bitmask::from_name("Flag3"), Some(Bitmask::Flag3));
+        assert_eq!(Bitmask::from_name("Flag9"), None);
+
+        assert_eq!("".parse::<Bitmask>(), Ok(Bitmask::none()));
+        assert_eq!("Flag1".parse::<Bitmask>(), Ok(Bitmask::Flag1));
+        assert_eq!(
+            " Flag1 | Flag3 ".parse::<Bitmask>(),
+            Ok(Bitmask::Flag1 | Bitmask::Flag3)
+        );
+        assert_eq!(
+            "Flag1 | 0x30 | 0b10 | 0o100 | 128".parse::<Bitmask>(),
+            Ok(Bitmask::Flag1
+                | Bitmask::Flag2
+                | Bitmask::Flag5
+                | Bitmask::Flag6
+                | Bitmask::Flag7
+                | Bitmask::Flag8)
+        );
+
+        assert!("0x+5".parse::<Bitmask>().is_err());
+        assert!("0b-1".parse::<Bitmask>().is_err());
+        assert!("0x".parse::<Bitmask>().is_err());
+
+        let input = "Flag1 | Flag9 | Flag3";
+        let err = input.parse::<Bitmask>().unwrap_err();
+        assert_eq!(err.span(), 8..13);
+        assert_eq!(err.token(input), "Flag9");
+        assert_eq!(err.to_string(), "unknown flag of Bitmask at bytes 8..13");
+
+        let err = "Flag1 || Flag3".parse::<Bitmask>().unwrap_err();
+        assert_eq!(err.span(), 7..7);
+        assert_eq!(err.to_string(), "expected flag of Bitmask at byte 7");
+
+        assert_eq!(
+            Bitmask::from_str_with("Flag1, Flag2;Flag4", &[',', ';'], false),
+            Ok(Bitmask::Flag1 | Bitmask::Flag2 | Bitmask::Flag4)
+        );
+        assert_eq!(
+            Bitmask::from_str_with("Flag1, 0x2", &[','], false)
+                .unwrap_err()
+                .span(),
+            7..10
+        );
+    }
+
     #[test]
     fn test_types() {
         #[bitmask(usize)]
@@ -211,8 +398,10 @@ mod tests {
         enum BitmaskCustom {
             Flag1,
             Flag2,
+            #[bitmask_flag(composite)]
             Flag12 = Self::Flag1.or(Self::Flag2).bits,
             Flag3,
+            #[bitmask_flag(composite)]
             Flag123 = Self::Flag12.or(Self::Flag3).bits,
             Flag4,
         }
@@ -227,8 +416,10 @@ mod tests {
         enum BitmaskCustomTyped {
             Flag1,
             Flag2,
+            #[bitmask_flag(composite)]
             Flag12 = Self::Flag1.or(Self::Flag2).bits,
             Flag3,
+            #[bitmask_flag(composite)]
             Flag123 = Self::Flag12.or(Self::Flag3).bits,
             Flag4,
         }
@@ -266,6 +457,133 @@ mod tests {
             BitmaskInverted::InvertedFlag4,
             BitmaskInverted::all_bits().xor(BitmaskInverted::Flag4)
         );
+
+        // inverted flags are not part of `all_flags`
+        assert_eq!(BitmaskInverted::all_flags(), 0b1111);
+        assert!(!BitmaskInverted::all_flags().is_all_bits());
+        assert_eq!(BitmaskInverted::InvertedFlag1.truncate(), 0b1110);
+        assert!(BitmaskInverted::all_bits().truncate().is_all_flags());
+        assert_eq!(BitmaskInverted::from_bits(!0b1), None);
+        assert_eq!(
+            BitmaskInverted::from_name("InvertedFlag1"),
+            Some(BitmaskInverted::InvertedFlag1)
+        );
+    }
+
+    #[test]
+    fn test_signed() {
+        #[bitmask(i8)]
+        #[bitmask_config(display)]
+        enum BitmaskI8 {
+            Flag1,
+            Flag2,
+            Flag3,
+            Flag4,
+            Flag5,
+            Flag6,
+            Flag7,
+            Sign,
+        }
+
+        assert_eq!(BitmaskI8::Sign.unsigned_bits(), 0x80);
+        assert_eq!(BitmaskI8::all_flags().unsigned_bits(), 0xff);
+        assert_eq!(
+            BitmaskI8::from_unsigned_bits(0x81),
+            BitmaskI8::Flag1 | BitmaskI8::Sign
+        );
+        assert_eq!(u8::from(BitmaskI8::Sign | BitmaskI8::Flag2), 0x82);
+        assert_eq!(BitmaskI8::from(0x81u8), BitmaskI8::Flag1 | BitmaskI8::Sign);
+        assert_eq!(BitmaskI8::from(-1i8), BitmaskI8::all_flags());
+        assert!(BitmaskI8::all_bits().is_all_flags());
+        assert_eq!(
+            BitmaskI8::Sign.iter().collect::<Vec<_>>(),
+            vec![BitmaskI8::Sign]
+        );
+
+        #[bitmask(i8)]
+        #[bitmask_config(strict)]
+        enum BitmaskI8Strict {
+            Flag1,
+            Sign = i8::MIN,
+        }
+
+        assert_eq!(
+            BitmaskI8Strict::try_from(0x81u8),
+            Ok(BitmaskI8Strict::Flag1 | BitmaskI8Strict::Sign)
+        );
+        assert!(BitmaskI8Strict::try_from(0x82u8).is_err());
+
+        assert!(BitmaskI8::Sign > BitmaskI8::Flag7);
+        assert!(BitmaskI8::all_bits() > BitmaskI8::none());
+        let mut masks = vec![BitmaskI8::Sign, BitmaskI8::none(), BitmaskI8::Flag1];
+        masks.sort();
+        assert_eq!(
+            masks,
+            vec![BitmaskI8::none(), BitmaskI8::Flag1, BitmaskI8::Sign]
+        );
+
+        assert_eq!(format!("{:?}", BitmaskI8::Sign), "BitmaskI8 { bits: 128 }");
+        assert_eq!(format!("{:#x}", BitmaskI8::Sign), "0x80");
+        assert_eq!(BitmaskI8::Sign.to_string(), "Sign");
+        assert_eq!("0x80".parse::<BitmaskI8>(), Ok(BitmaskI8::Sign));
+        assert_eq!("255".parse::<BitmaskI8>(), Ok(BitmaskI8::all_bits()));
+        assert!("0x100".parse::<BitmaskI8>().is_err());
+    }
+
+    #[test]
+    fn test_strict() {
+        #[bitmask(u8)]
+        #[bitmask_config(strict, inverted_flags)]
+        enum BitmaskStrict {
+            Flag1,
+            Flag2,
+            Flag3,
+        }
+
+        assert_eq!(
+            !BitmaskStrict::Flag1,
+            BitmaskStrict::Flag2 | BitmaskStrict::Flag3
+        );
+        assert_eq!(BitmaskStrict::InvertedFlag1, 0b110);
+        assert_eq!(BitmaskStrict::all_bits(), BitmaskStrict::all_flags());
+        assert!(BitmaskStrict::all_flags().is_all_bits());
+        assert_eq!(!BitmaskStrict::none(), 0b111);
+        assert_eq!(BitmaskStrict::Flag2.complement(), !BitmaskStrict::Flag2);
+
+        let mut bm = BitmaskStrict::Flag1;
+        bm ^= BitmaskStrict::all_bits();
+        assert_eq!(bm, 0b110);
+        bm.toggle(!bm);
+        assert!(bm.is_all_flags());
+
+        assert_eq!(
+            BitmaskStrict::try_from(0b101),
+            Ok(BitmaskStrict::Flag1 | BitmaskStrict::Flag3)
+        );
+        assert_eq!(
+            BitmaskStrict::try_from(0b1001).map_err(|err| err.unknown_bits()),
+            Err(0b1000)
+        );
+        assert_eq!(
+            BitmaskStrict::from_bits_truncate(0b1001),
+            BitmaskStrict::Flag1
+        );
+
+        assert_eq!(
+            "Flag1 | 0x2".parse::<BitmaskStrict>(),
+            Ok(BitmaskStrict::from_bits_truncate(0b11))
+        );
+        assert!("Flag1 | 0x8".parse::<BitmaskStrict>().is_err());
+
+        #[bitmask(i8)]
+        #[bitmask_config(strict)]
+        enum BitmaskStrictI8 {
+            Flag1,
+            Flag2,
+        }
+
+        assert_eq!((!BitmaskStrictI8::Flag1).unsigned_bits(), 0b10);
+        assert!(BitmaskStrictI8::all_bits() > BitmaskStrictI8::Flag1);
     }
 
     #[test]
@@ -305,8 +623,10 @@ mod tests {
         enum BitmaskCustom {
             Flag1,
             Flag2,
+            #[bitmask_flag(composite)]
             Flag12 = Self::Flag1.or(Self::Flag2).bits,
             Flag3,
+            #[bitmask_flag(composite)]
             Flag123 = Self::Flag12.or(Self::Flag3).bits,
             Flag4,
         }
@@ -328,8 +648,10 @@ mod tests {
         enum BitmaskCustomTyped {
             Flag1,
             Flag2,
+            #[bitmask_flag(composite)]
             Flag12 = Self::Flag1.or(Self::Flag2).bits,
             Flag3,
+            #[bitmask_flag(composite)]
             Flag123 = Self::Flag12.or(Self::Flag3).bits,
             Flag4,
         }
@@ -354,6 +676,7 @@ mod tests {
         pub enum BitmaskVecDebug {
             Flag1,
             Flag2,
+            #[bitmask_flag(composite)]
             Flag12 = Self::Flag1.or(Self::Flag2).bits,
             Flag3,
         }
@@ -372,7 +695,7 @@ mod tests {
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::Flag12),
-            "BitmaskVecDebug[Flag1, Flag2, Flag12]"
+            "BitmaskVecDebug[Flag12]"
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::Flag3),
@@ -384,7 +707,19 @@ mod tests {
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::all_flags()),
-            "BitmaskVecDebug[Flag1, Flag2, Flag12, Flag3]"
+            "BitmaskVecDebug[Flag12, Flag3]"
+        );
+        assert_eq!(
+            format!("{:?}", BitmaskVecDebug::from(0b11101)),
+            "BitmaskVecDebug[Flag1, Flag3, 0x18]"
+        );
+        assert_eq!(
+            format!("{:#?}", BitmaskVecDebug::from(0b1111)),
+            "BitmaskVecDebug {\n    flags: [\n        Flag12,\n        Flag3,\n        0x8,\n    ],\n    bits: 0xf,\n}"
+        );
+        assert_eq!(
+            format!("{:#?}", BitmaskVecDebug::none()),
+            "BitmaskVecDebug {\n    flags: [],\n    bits: 0x0,\n}"
         );
 
         // default formatting
@@ -421,15 +756,375 @@ mod tests {
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::InvertedFlag1),
-            "BitmaskVecDebug[InvertedFlag1, Flag2]"
+            "BitmaskVecDebug[Flag2, 0xfc]"
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::InvertedFlag2),
-            "BitmaskVecDebug[Flag1, InvertedFlag2]"
+            "BitmaskVecDebug[Flag1, 0xfc]"
         );
         assert_eq!(
             format!("{:?}", BitmaskVecDebug::all_flags()),
-            "BitmaskVecDebug[Flag1, InvertedFlag1, Flag2, InvertedFlag2]"
+            "BitmaskVecDebug[Flag1, Flag2]"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        #[bitmask(u8)]
+        #[bitmask_config(display)]
+        enum BitmaskDisplay {
+            Flag1,
+            Flag2,
+            #[bitmask_flag(composite)]
+            Flag12 = Self::Flag1.or(Self::Flag2).bits,
+            Flag3,
+        }
+
+        let cases = [
+            (BitmaskDisplay::none(), "(empty)"),
+            (BitmaskDisplay::Flag2, "Flag2"),
+            (
+                BitmaskDisplay::Flag1 | BitmaskDisplay::Flag3,
+                "Flag1 | Flag3",
+            ),
+            (BitmaskDisplay::Flag12, "Flag1 | Flag2 | Flag12"),
+            (BitmaskDisplay::from(0b1000_0100), "Flag3 | 0x80"),
+            (BitmaskDisplay::from(0b1111_0000), "0xf0"),
+        ];
+        for (bm, s) in cases {
+            assert_eq!(bm.to_string(), s);
+            assert_eq!(s.parse::<BitmaskDisplay>(), Ok(bm));
+        }
+    }
+
+    #[test]
+    fn test_cfg() {
+        #[bitmask(u8)]
+        #[bitmask_config(vec_debug, flags_iter)]
+        enum BitmaskCfg {
+            Flag1,
+            #[cfg(not(test))]
+            Disabled,
+            Flag2,
+            #[cfg(test)]
+            Enabled,
+            #[cfg(not(test))]
+            #[bitmask_flag(composite)]
+            DisabledComposite = 0,
+        }
+
+        assert_eq!(BitmaskCfg::Flag1, 0b001);
+        assert_eq!(BitmaskCfg::Flag2, 0b010);
+        assert_eq!(BitmaskCfg::Enabled, 0b100);
+        assert_eq!(BitmaskCfg::all_flags(), 0b111);
+        assert_eq!(BitmaskCfg::flags().count(), 3);
+        assert_eq!(BitmaskCfg::all_bits().iter().count(), 3);
+        assert_eq!(
+            format!("{:?}", BitmaskCfg::all_flags()),
+            "BitmaskCfg[Flag1, Flag2, Enabled]"
+        );
+        assert_eq!(BitmaskCfg::from_name("Disabled"), None);
+
+        #[bitmask(u8)]
+        #[bitmask_config(flags_iter, inverted_flags)]
+        enum BitmaskCfgInverted {
+            #[cfg(not(test))]
+            Disabled,
+            Flag1,
+        }
+
+        assert_eq!(BitmaskCfgInverted::Flag1, 0b1);
+        assert_eq!(
+            BitmaskCfgInverted::flags().copied().collect::<Vec<_>>(),
+            vec![("Flag1", BitmaskCfgInverted::Flag1)],
+        );
+        assert_eq!(
+            BitmaskCfgInverted::inverted_flags()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![("InvertedFlag1", BitmaskCfgInverted::InvertedFlag1)],
+        );
+    }
+
+    #[test]
+    fn test_atomic() {
+        use std::sync::atomic::Ordering;
+
+        #[bitmask(u8)]
+        #[bitmask_config(atomic)]
+        enum BitmaskAtomic {
+            Flag1,
+            Flag2,
+            Flag3,
+        }
+
+        let atomic = AtomicBitmaskAtomic::new(BitmaskAtomic::Flag1);
+        assert_eq!(atomic.load(Ordering::Relaxed), BitmaskAtomic::Flag1);
+
+        std::thread::scope(|s| {
+            s.spawn(|| atomic.fetch_insert(BitmaskAtomic::Flag2, Ordering::Relaxed));
+            s.spawn(|| atomic.fetch_insert(BitmaskAtomic::Flag3, Ordering::Relaxed));
+        });
+        assert!(atomic.load(Ordering::Relaxed).is_all_flags());
+
+        let prev = atomic.fetch_remove(BitmaskAtomic::Flag1, Ordering::Relaxed);
+        assert!(prev.is_all_flags());
+        let prev = atomic.fetch_toggle(
+            BitmaskAtomic::Flag1 | BitmaskAtomic::Flag2,
+            Ordering::Relaxed,
+        );
+        assert_eq!(prev, BitmaskAtomic::Flag2 | BitmaskAtomic::Flag3);
+        assert_eq!(
+            atomic.load(Ordering::Relaxed),
+            BitmaskAtomic::Flag1 | BitmaskAtomic::Flag3
+        );
+
+        assert_eq!(
+            atomic.compare_exchange(
+                BitmaskAtomic::Flag1,
+                BitmaskAtomic::none(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ),
+            Err(BitmaskAtomic::Flag1 | BitmaskAtomic::Flag3)
+        );
+        assert_eq!(
+            atomic.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bm| {
+                Some(bm.without(BitmaskAtomic::Flag3))
+            }),
+            Ok(BitmaskAtomic::Flag1 | BitmaskAtomic::Flag3)
+        );
+
+        atomic.store(BitmaskAtomic::Flag2, Ordering::Relaxed);
+        assert_eq!(
+            atomic.swap(BitmaskAtomic::none(), Ordering::Relaxed),
+            BitmaskAtomic::Flag2
+        );
+        assert_eq!(atomic.into_inner(), BitmaskAtomic::none());
+        assert_eq!(
+            format!("{:?}", AtomicBitmaskAtomic::default()),
+            "BitmaskAtomic { bits: 0 }"
+        );
+    }
+
+    #[test]
+    fn test_flag_info() {
+        #[bitmask(u16)]
+        #[bitmask_config(inverted_flags)]
+        enum BitmaskInfo {
+            /// The first flag.
+            Flag1,
+            #[cfg(not(test))]
+            Disabled,
+            /// The second flag,
+            /// with a longer description.
+            Flag2 = 1 << 8,
+            #[bitmask_flag(composite)]
+            Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        }
+
+        const INFO: BitmaskInfoFlagInfo = BitmaskInfo::FLAG_INFO[2];
+        assert_eq!(INFO.name(), "Flag2");
+        assert_eq!(INFO.value(), BitmaskInfo::Flag2);
+        assert_eq!(INFO.bit_index(), Some(8));
+        assert!(!INFO.is_composite());
+        assert!(!INFO.is_inverted());
+        assert_eq!(INFO.doc(), "The second flag,\nwith a longer description.");
+
+        assert_eq!(BitmaskInfo::FLAG_COUNT, 6);
+        assert_eq!(BitmaskInfo::FLAG_INFO.len(), BitmaskInfo::FLAG_COUNT);
+        assert_eq!(BitmaskInfo::BITS, 16);
+        assert_eq!(
+            BitmaskInfo::FLAG_INFO
+                .iter()
+                .map(|info| (info.name(), info.bit_index(), info.is_inverted()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Flag1", Some(0), false),
+                ("InvertedFlag1", None, true),
+                ("Flag2", Some(8), false),
+                ("InvertedFlag2", None, true),
+                ("Flag12", None, false),
+                ("InvertedFlag12", None, true),
+            ]
+        );
+        assert_eq!(BitmaskInfo::FLAG_INFO[1].doc(), "The first flag.");
+        assert!(BitmaskInfo::FLAG_INFO[4].is_composite());
+        assert!(BitmaskInfo::FLAG_INFO[5].is_composite());
+        assert!(!BitmaskInfo::FLAG_INFO[1].is_composite());
+        assert_eq!(BitmaskInfo::FLAG_INFO[4].doc(), "");
+
+        #[bitmask(u8)]
+        enum BitmaskComposite {
+            Flag1,
+            #[bitmask_flag(composite)]
+            Alias1 = 0b1,
+            #[bitmask_flag(composite)]
+            Zero = 0,
+        }
+
+        assert_eq!(
+            BitmaskComposite::FLAG_INFO
+                .iter()
+                .map(|info| (info.name(), info.bit_index(), info.is_composite()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Flag1", Some(0), false),
+                ("Alias1", Some(0), true),
+                ("Zero", None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flag_enum() {
+        #[bitmask(u8)]
+        #[bitmask_config(flag_enum)]
+        enum BitmaskEnum {
+            Flag1,
+            Flag2,
+            #[cfg(not(test))]
+            Disabled,
+            #[bitmask_flag(composite)]
+            Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        }
+
+        fn describe(flag: BitmaskEnumFlag) -> &'static str {
+            match flag {
+                BitmaskEnumFlag::Flag1 => "one",
+                BitmaskEnumFlag::Flag2 => "two",
+                BitmaskEnumFlag::Flag12 => "both",
+            }
+        }
+
+        assert_eq!(
+            BitmaskEnum::from(BitmaskEnumFlag::Flag2),
+            BitmaskEnum::Flag2
+        );
+        assert_eq!(
+            BitmaskEnumFlag::try_from(BitmaskEnum::Flag12),
+            Ok(BitmaskEnumFlag::Flag12)
+        );
+        assert_eq!(
+            BitmaskEnumFlag::try_from(BitmaskEnum::none()),
+            Err(BitmaskEnum::none())
+        );
+        assert_eq!(BitmaskEnumFlag::Flag1.name(), "Flag1");
+        assert_eq!(
+            BitmaskEnum::all_flags()
+                .iter_flags()
+                .map(describe)
+                .collect::<Vec<_>>(),
+            vec!["one", "two", "both"],
+        );
+
+        let flags = [BitmaskEnumFlag::Flag1, BitmaskEnumFlag::Flag2];
+        assert_eq!(flags.iter().collect::<BitmaskEnum>(), BitmaskEnum::Flag12);
+        assert_eq!(flags.into_iter().sum::<BitmaskEnum>(), BitmaskEnum::Flag12);
+
+        let mut bm = BitmaskEnum::none();
+        bm.extend([BitmaskEnumFlag::Flag2]);
+        assert_eq!(bm, BitmaskEnum::Flag2);
+    }
+
+    #[test]
+    fn test_config_key_value() {
+        #[bitmask(u8)]
+        #[bitmask_config(inverted_prefix = "Not", debug = "vec", display = "names")]
+        enum BitmaskKeyValue {
+            Flag1,
+            Flag2,
+        }
+
+        assert_eq!(BitmaskKeyValue::NotFlag1, 0b11111110);
+        assert_eq!(
+            format!("{:?}", BitmaskKeyValue::Flag2),
+            "BitmaskKeyValue[Flag2]"
+        );
+        assert_eq!(BitmaskKeyValue::Flag2.to_string(), "Flag2");
+
+        #[bitmask(u8)]
+        #[bitmask_config(debug = "struct")]
+        enum BitmaskStructDebug {
+            Flag1,
+        }
+
+        assert_eq!(
+            format!("{:?}", BitmaskStructDebug::Flag1),
+            "BitmaskStructDebug { bits: 1 }"
+        );
+    }
+
+    #[test]
+    fn test_derive_config() {
+        use std::cmp::Ordering;
+
+        #[bitmask(u8)]
+        #[bitmask_config(no_derive(PartialOrd, Ord, Hash), default)]
+        enum BitmaskByCount {
+            Flag1,
+            Flag2,
+            Flag3,
+        }
+
+        impl PartialOrd for BitmaskByCount {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for BitmaskByCount {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.bits().count_ones().cmp(&other.bits().count_ones())
+            }
+        }
+
+        assert!(BitmaskByCount::Flag3 < BitmaskByCount::Flag1 | BitmaskByCount::Flag2);
+        assert_eq!(BitmaskByCount::default(), BitmaskByCount::none());
+
+        #[bitmask(u8)]
+        #[bitmask_config(default(Flag1, Flag3))]
+        enum BitmaskDefault {
+            Flag1,
+            Flag2,
+            Flag3,
+        }
+
+        assert_eq!(
+            BitmaskDefault::default(),
+            BitmaskDefault::Flag1 | BitmaskDefault::Flag3
+        );
+
+        #[bitmask(i8)]
+        #[bitmask_config(default, no_derive(Ord))]
+        enum BitmaskDefaultNone {
+            Flag1,
+        }
+
+        assert_eq!(BitmaskDefaultNone::default(), BitmaskDefaultNone::none());
+        assert_eq!(
+            BitmaskDefaultNone::all_bits().partial_cmp(&BitmaskDefaultNone::Flag1),
+            Some(Ordering::Greater)
+        );
+
+        #[bitmask(u8)]
+        #[bitmask_config(no_derive(Eq, Ord, PartialOrd), flags_iter, vec_debug, display)]
+        enum BitmaskPartialEq {
+            Flag1,
+            Flag2,
+        }
+
+        assert_eq!(
+            BitmaskPartialEq::Flag1 | BitmaskPartialEq::Flag2,
+            BitmaskPartialEq::all_flags()
+        );
+        assert_eq!(
+            BitmaskPartialEq::FLAG_INFO[0],
+            BitmaskPartialEq::FLAG_INFO[0]
+        );
+        assert_ne!(
+            BitmaskPartialEq::FLAG_INFO[0],
+            BitmaskPartialEq::FLAG_INFO[1]
         );
     }
 
@@ -455,6 +1150,7 @@ mod tests {
             Flag1,
             Flag2,
             Flag3,
+            #[bitmask_flag(composite)]
             FlagCustom = 123,
         }
 
@@ -475,6 +1171,7 @@ mod tests {
         #[bitmask_config(flags_iter, inverted_flags)]
         pub enum BitmaskFlagsIter {
             Flag1,
+            #[bitmask_flag(composite)]
             FlagCustom = 123,
         }
 
@@ -482,10 +1179,86 @@ mod tests {
             BitmaskFlagsIter::flags().copied().collect::<Vec<_>>(),
             vec![
                 ("Flag1", BitmaskFlagsIter::Flag1),
-                ("InvertedFlag1", BitmaskFlagsIter::InvertedFlag1),
                 ("FlagCustom", BitmaskFlagsIter::FlagCustom),
+            ],
+        );
+        assert_eq!(
+            BitmaskFlagsIter::inverted_flags()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![
+                ("InvertedFlag1", BitmaskFlagsIter::InvertedFlag1),
                 ("InvertedFlagCustom", BitmaskFlagsIter::InvertedFlagCustom),
             ],
         );
     }
+
+    #[test]
+    fn test_iter() {
+        let bm = Bitmask::Flag2 | Bitmask::Flag5 | Bitmask::Flag8;
+        assert_eq!(
+            bm.iter().collect::<Vec<_>>(),
+            vec![Bitmask::Flag2, Bitmask::Flag5, Bitmask::Flag8],
+        );
+        assert_eq!(
+            bm.iter_names().collect::<Vec<_>>(),
+            vec![
+                ("Flag2", Bitmask::Flag2),
+                ("Flag5", Bitmask::Flag5),
+                ("Flag8", Bitmask::Flag8),
+            ],
+        );
+
+        assert_eq!(Bitmask::none().iter().next(), None);
+        assert_eq!(Bitmask::all_bits().iter().count(), 8);
+
+        let mut flags = Vec::new();
+        for flag in bm {
+            flags.push(flag);
+        }
+        for flag in &bm {
+            flags.push(flag);
+        }
+        assert_eq!(flags.len(), 6);
+    }
+
+    #[test]
+    fn test_iter_composite() {
+        #[bitmask(u8)]
+        #[bitmask_config(inverted_flags)]
+        enum BitmaskComposite {
+            #[bitmask_flag(composite)]
+            Flag13 = 0b101,
+            Flag1,
+            Flag2,
+            Flag3,
+            #[bitmask_flag(composite)]
+            Alias1 = 0b1,
+            #[bitmask_flag(composite)]
+            Zero = 0,
+            #[bitmask_flag(composite)]
+            Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        }
+
+        assert_eq!(
+            BitmaskComposite::all_flags()
+                .iter_names()
+                .collect::<Vec<_>>(),
+            vec![
+                ("Flag1", BitmaskComposite::Flag1),
+                ("Flag2", BitmaskComposite::Flag2),
+                ("Flag3", BitmaskComposite::Flag3),
+                ("Flag13", BitmaskComposite::Flag13),
+                ("Flag12", BitmaskComposite::Flag12),
+            ],
+        );
+        assert_eq!(
+            BitmaskComposite::Flag12.iter().collect::<Vec<_>>(),
+            vec![
+                BitmaskComposite::Flag1,
+                BitmaskComposite::Flag2,
+                BitmaskComposite::Flag12,
+            ],
+        );
+    }
 }
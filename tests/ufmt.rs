@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+
+    #[bitmask(u8)]
+    #[bitmask_config(ufmt)]
+    enum BitmaskUfmt {
+        Flag1,
+        Flag2,
+        #[bitmask_flag(composite)]
+        Flag12 = Self::Flag1.or(Self::Flag2).bits,
+        Flag3,
+    }
+
+    #[bitmask(i16)]
+    #[bitmask_config(ufmt)]
+    enum BitmaskUfmtSigned {
+        Flag1,
+        Flag16 = i16::MIN,
+    }
+
+    #[bitmask([u8; 3])]
+    #[bitmask_config(ufmt)]
+    enum BitmaskUfmtWide {
+        Flag1,
+        Flag24 = [0, 0, 0b1000_0000],
+    }
+
+    fn debug<T: ufmt::uDebug>(bm: T) -> String {
+        let mut s = String::new();
+        ufmt::uwrite!(s, "{:?}", bm).unwrap();
+        s
+    }
+
+    fn display<T: ufmt::uDisplay>(bm: T) -> String {
+        let mut s = String::new();
+        ufmt::uwrite!(s, "{}", bm).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(debug(BitmaskUfmt::none()), "BitmaskUfmt[]");
+        assert_eq!(debug(BitmaskUfmt::Flag1), "BitmaskUfmt[Flag1]");
+        assert_eq!(
+            debug(BitmaskUfmt::Flag1 | BitmaskUfmt::Flag2),
+            "BitmaskUfmt[Flag12]"
+        );
+        assert_eq!(
+            debug(BitmaskUfmt::Flag12 | BitmaskUfmt::Flag3),
+            "BitmaskUfmt[Flag12, Flag3]"
+        );
+        assert_eq!(
+            debug(BitmaskUfmt::Flag3 | BitmaskUfmt::from(0b1010_0000)),
+            "BitmaskUfmt[Flag3, 0xa0]"
+        );
+        assert_eq!(debug(BitmaskUfmt::from(0x10)), "BitmaskUfmt[0x10]");
+
+        assert_eq!(
+            debug(BitmaskUfmtSigned::Flag16),
+            "BitmaskUfmtSigned[Flag16]"
+        );
+        assert_eq!(
+            debug(BitmaskUfmtSigned::Flag1 | BitmaskUfmtSigned::from(-0x7ff0i16)),
+            "BitmaskUfmtSigned[Flag1, Flag16, 0x10]"
+        );
+
+        assert_eq!(
+            debug(BitmaskUfmtWide::Flag24 | BitmaskUfmtWide::from([0, 0x01, 0x20])),
+            "BitmaskUfmtWide[Flag24, 0x200100]"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(display(BitmaskUfmt::none()), "(empty)");
+        assert_eq!(
+            display(BitmaskUfmt::Flag12 | BitmaskUfmt::Flag3),
+            "Flag1 | Flag2 | Flag3 | Flag12"
+        );
+        assert_eq!(
+            display(BitmaskUfmt::Flag1 | BitmaskUfmt::from(0xf0)),
+            "Flag1 | 0xf0"
+        );
+        assert_eq!(display(BitmaskUfmt::from(0x80)), "0x80");
+
+        assert_eq!(
+            display(BitmaskUfmtSigned::Flag16 | BitmaskUfmtSigned::from(0x0f00i16)),
+            "Flag16 | 0xf00"
+        );
+
+        assert_eq!(
+            display(BitmaskUfmtWide::Flag1 | BitmaskUfmtWide::from([0, 0xab, 0])),
+            "Flag1 | 0xab00"
+        );
+    }
+}
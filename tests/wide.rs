@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+
+    #[bitmask([u8; 3])]
+    #[bitmask_config(vec_debug, display)]
+    enum Wide {
+        Flag0,
+        Flag1,
+        Flag2,
+        Flag3,
+        Flag4,
+        Flag5,
+        Flag6,
+        Flag7,
+        Flag8,
+        Flag9,
+        #[bitmask_flag(composite)]
+        Flag09 = Self::Flag0.or(Self::Flag9).bits,
+        Flag23 = [0, 0, 0b1000_0000],
+    }
+
+    #[bitmask([u64; 4])]
+    #[bitmask_config(inverted_flags, flags_iter, serde)]
+    enum Wide256 {
+        First,
+        Last = [0, 0, 0, 1 << 63],
+    }
+
+    const CONST_WIDE: Wide = Wide::Flag1.or(Wide::Flag8).without(Wide::Flag1);
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(Wide::Flag0, [0b1, 0, 0]);
+        assert_eq!(Wide::Flag7, [0b1000_0000, 0, 0]);
+        assert_eq!(Wide::Flag8, [0, 0b1, 0]);
+        assert_eq!(Wide::Flag09, [0b1, 0b10, 0]);
+        assert_eq!(CONST_WIDE, Wide::Flag8);
+
+        assert_eq!(Wide::all_flags(), [0xff, 0b11, 0b1000_0000]);
+        assert_eq!(Wide::all_bits(), [0xff, 0xff, 0xff]);
+        assert!(Wide::all_bits().is_all_bits());
+        assert!(Wide::all_bits().truncate().is_all_flags());
+        assert!(Wide::none().is_none());
+
+        assert_eq!(Wide::BITS, 24);
+        assert_eq!(Wide::FLAG_INFO[11].bit_index(), Some(23));
+        assert!(Wide::FLAG_INFO[10].is_composite());
+        assert_eq!(Wide256::FLAG_INFO[2].bit_index(), Some(255));
+
+        assert_eq!(Wide256::InvertedFirst, [!1, !0, !0, !0]);
+        assert_eq!(Wide256::InvertedLast.bits()[3], !0 >> 1);
+    }
+
+    #[test]
+    fn test_ops() {
+        let mut bm = Wide::Flag1 | Wide::Flag9;
+        assert!(bm.contains(Wide::Flag9));
+        assert!(!bm.contains(Wide::Flag09));
+        assert!(bm.intersects(Wide::Flag09));
+        assert!(!bm.intersects(Wide::Flag23));
+
+        bm |= Wide::Flag23;
+        assert_eq!(bm, [0b10, 0b10, 0b1000_0000]);
+
+        bm &= !Wide::Flag1;
+        assert_eq!(bm, [0, 0b10, 0b1000_0000]);
+
+        bm ^= Wide::Flag09;
+        assert_eq!(bm, [0b1, 0, 0b1000_0000]);
+
+        assert_eq!(bm - Wide::Flag23, Wide::Flag0);
+        assert_eq!(bm - [0b1, 0, 0], Wide::Flag23);
+
+        bm.set(Wide::Flag9, true);
+        bm.remove(Wide::Flag0);
+        assert_eq!(bm, Wide::Flag9 | Wide::Flag23);
+
+        assert_eq!(Wide::from_bits([0, 0b100, 0]), None);
+        assert_eq!(Wide::from_bits_truncate([0, 0b110, 0]), Wide::Flag9);
+    }
+
+    #[test]
+    fn test_iter() {
+        assert_eq!(
+            (Wide::Flag09 | Wide::Flag23).iter().collect::<Vec<_>>(),
+            vec![Wide::Flag0, Wide::Flag9, Wide::Flag23, Wide::Flag09],
+        );
+    }
+
+    #[test]
+    fn test_fmt() {
+        let bm = Wide::Flag0 | Wide::Flag23;
+        assert_eq!(format!("{:x}", bm), "800001");
+        assert_eq!(format!("{:#X}", Wide::all_bits()), "0xFFFFFF");
+        assert_eq!(format!("{:#b}", Wide::Flag9), "0b1000000000");
+        assert_eq!(format!("{:o}", Wide::Flag9), "1000");
+        assert_eq!(format!("{:08x}", Wide::none()), "00000000");
+
+        assert_eq!(format!("{:?}", bm), "Wide[Flag0, Flag23]");
+        assert_eq!(
+            format!("{:?}", Wide::Flag09 | Wide::from([0, 0, 1])),
+            "Wide[Flag09, 0x10000]"
+        );
+        assert_eq!(bm.to_string(), "Flag0 | Flag23");
+        assert_eq!(Wide::from([0, 0, 0b11]).to_string(), "0x30000");
+        assert_eq!(
+            format!("{:x}", Wide256::Last | Wide256::First),
+            format!("8{:063x}", 1)
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "Flag0 | Flag23".parse::<Wide>(),
+            Ok(Wide::Flag0 | Wide::Flag23)
+        );
+        assert_eq!("0x800001".parse::<Wide>(), Ok(Wide::Flag0 | Wide::Flag23));
+        assert_eq!("512".parse::<Wide>(), Ok(Wide::Flag9));
+        assert!("0x1000000".parse::<Wide>().is_err());
+    }
+
+    #[test]
+    fn test_ord() {
+        assert!(Wide::Flag8 > Wide::Flag0);
+        assert!(Wide::Flag23 > Wide::Flag8 | Wide::Flag7);
+        assert!(Wide256::Last > Wide256::First);
+
+        let mut flags = [Wide::Flag23, Wide::Flag0, Wide::Flag09, Wide::Flag8];
+        flags.sort();
+        assert_eq!(
+            flags,
+            [Wide::Flag0, Wide::Flag8, Wide::Flag09, Wide::Flag23]
+        );
+    }
+}
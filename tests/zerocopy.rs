@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use bitmask_enum::bitmask;
+    use zerocopy::{FromBytes, IntoBytes};
+
+    #[bitmask(u8)]
+    #[bitmask_config(zerocopy)]
+    enum BitmaskZerocopy {
+        Flag1,
+        Flag2,
+    }
+
+    #[bitmask([u16; 2])]
+    #[bitmask_config(zerocopy)]
+    enum BitmaskZerocopyWide {
+        Flag1,
+        Flag32 = [0, 1 << 15],
+    }
+
+    #[bitmask(u8)]
+    #[bitmask_config(zerocopy, derive(zerocopy::Unaligned))]
+    enum BitmaskZerocopyUnaligned {
+        Flag1,
+    }
+
+    #[test]
+    fn test_derive() {
+        fn unaligned<T: zerocopy::Unaligned>() {}
+        unaligned::<BitmaskZerocopyUnaligned>();
+    }
+
+    #[test]
+    fn test_cast() {
+        let bytes = [0b01, 0b10, 0b11, 0x80];
+        let masks = <[BitmaskZerocopy]>::ref_from_bytes(&bytes).unwrap();
+        assert_eq!(
+            masks,
+            [
+                BitmaskZerocopy::Flag1,
+                BitmaskZerocopy::Flag2,
+                BitmaskZerocopy::Flag1 | BitmaskZerocopy::Flag2,
+                BitmaskZerocopy::from(0x80),
+            ]
+        );
+        assert_eq!(masks.as_bytes(), bytes);
+
+        let wide =
+            BitmaskZerocopyWide::read_from_bytes(&[[0x01, 0x00], [0x00, 0x80]].concat()).unwrap();
+        assert_eq!(
+            wide,
+            BitmaskZerocopyWide::Flag1 | BitmaskZerocopyWide::Flag32
+        );
+    }
+}